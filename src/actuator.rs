@@ -0,0 +1,230 @@
+// actuator.rs
+// 执行器输出子系统：把 `UnifiedControlMessage` 里的统一控制状态，按固定频率映射为
+// 具体设备帧（CAN 总线 / 串口 PWM），真正驱动机器本体，而不仅仅是解析遥操作消息。
+// 类比远程桌面把收到的输入事件转换为本地设备动作——这里把收到的控制状态转换为
+// 执行器指令帧。
+
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+/// 单通道的缩放/限幅/死区配置：`raw -> (raw.abs() < deadband ? 0 : raw) * scale + offset`，
+/// 结果再 clamp 到 `[min, max]`。
+#[derive(Debug, Clone, Copy)]
+pub struct ChannelConfig {
+    pub scale: f64,
+    pub offset: f64,
+    pub min: f64,
+    pub max: f64,
+    pub deadband: f64,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self { scale: 1.0, offset: 0.0, min: -1.0, max: 1.0, deadband: 0.02 }
+    }
+}
+
+impl ChannelConfig {
+    /// 从环境变量加载：`<prefix>_SCALE` / `_OFFSET` / `_MIN` / `_MAX` / `_DEADBAND`，缺省保留默认值。
+    pub fn from_env(prefix: &str) -> Self {
+        let mut cfg = Self::default();
+        if let Some(v) = env_f64(&format!("{prefix}_SCALE")) { cfg.scale = v; }
+        if let Some(v) = env_f64(&format!("{prefix}_OFFSET")) { cfg.offset = v; }
+        if let Some(v) = env_f64(&format!("{prefix}_MIN")) { cfg.min = v; }
+        if let Some(v) = env_f64(&format!("{prefix}_MAX")) { cfg.max = v; }
+        if let Some(v) = env_f64(&format!("{prefix}_DEADBAND")) { cfg.deadband = v; }
+        cfg
+    }
+
+    pub fn apply(&self, raw: f64) -> f64 {
+        let deadbanded = if raw.abs() < self.deadband { 0.0 } else { raw };
+        (deadbanded * self.scale + self.offset).clamp(self.min, self.max)
+    }
+}
+
+fn env_f64(name: &str) -> Option<f64> {
+    std::env::var(name).ok().and_then(|s| s.parse().ok())
+}
+
+/// 各通道的执行器配置，分别可通过 `ACT_<CHANNEL>_*` 环境变量覆盖。
+pub struct ActuatorConfig {
+    pub rotation: ChannelConfig,
+    pub throttle: ChannelConfig,
+    pub brake: ChannelConfig,
+    pub boom: ChannelConfig,
+    pub bucket: ChannelConfig,
+    pub swing: ChannelConfig,
+    pub stick: ChannelConfig,
+    pub left_track: ChannelConfig,
+    pub right_track: ChannelConfig,
+    /// 控制消息超过该时长（毫秒）未更新时，视为失联并清零所有输出。
+    pub watchdog_ms: i64,
+    /// 执行器指令下发频率。
+    pub rate_hz: f64,
+}
+
+impl Default for ActuatorConfig {
+    fn default() -> Self {
+        Self {
+            rotation: ChannelConfig::from_env("ACT_ROTATION"),
+            throttle: ChannelConfig::from_env("ACT_THROTTLE"),
+            brake: ChannelConfig::from_env("ACT_BRAKE"),
+            boom: ChannelConfig::from_env("ACT_BOOM"),
+            bucket: ChannelConfig::from_env("ACT_BUCKET"),
+            swing: ChannelConfig::from_env("ACT_SWING"),
+            stick: ChannelConfig::from_env("ACT_STICK"),
+            left_track: ChannelConfig::from_env("ACT_LEFT_TRACK"),
+            right_track: ChannelConfig::from_env("ACT_RIGHT_TRACK"),
+            watchdog_ms: std::env::var("ACTUATOR_WATCHDOG_MS").ok().and_then(|s| s.parse().ok()).unwrap_or(500),
+            rate_hz: std::env::var("ACTUATOR_RATE_HZ").ok().and_then(|s| s.parse().ok()).unwrap_or(50.0),
+        }
+    }
+}
+
+/// 一帧已按通道配置做完缩放/限幅/死区处理的执行器指令。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ActuatorFrame {
+    pub rotation: f64,
+    pub throttle: f64,
+    pub brake: f64,
+    pub boom: f64,
+    pub bucket: f64,
+    pub swing: f64,
+    pub stick: f64,
+    pub left_track: f64,
+    pub right_track: f64,
+    pub gear: u8, // 0=P 1=R 2=N 3=D，未知档位按 N 处理
+}
+
+/// 档位字符串（'P'|'R'|'N'|'D'）映射为设备帧里使用的数值编码，未知档位按空档处理。
+pub fn gear_code(gear: &str) -> u8 {
+    match gear {
+        "P" => 0,
+        "R" => 1,
+        "D" => 3,
+        _ => 2, // 'N' 及未知档位
+    }
+}
+
+impl ActuatorFrame {
+    pub fn zeroed() -> Self {
+        Self { gear: 2, ..Default::default() }
+    }
+}
+
+/// 执行器输出后端：把一帧已归一化的控制指令写到具体设备上。
+pub trait ActuatorSink: Send {
+    /// 下发一帧指令。
+    fn apply(&mut self, frame: &ActuatorFrame) -> Result<()>;
+    /// 看门狗超时或启动前的安全态：所有通道清零、空档。
+    fn zero(&mut self) -> Result<()> {
+        self.apply(&ActuatorFrame::zeroed())
+    }
+}
+
+/// 仅打印帧内容，不接触任何真实设备；用于联调 DataChannel/ROS2 控制链路。
+pub struct DryRunSink;
+
+impl ActuatorSink for DryRunSink {
+    fn apply(&mut self, frame: &ActuatorFrame) -> Result<()> {
+        println!(
+            "🧪 [dry-run] rot={:.2} thr={:.2} brk={:.2} boom={:.2} bkt={:.2} swing={:.2} stick={:.2} lt={:.2} rt={:.2} gear={}",
+            frame.rotation, frame.throttle, frame.brake, frame.boom, frame.bucket, frame.swing, frame.stick, frame.left_track, frame.right_track, frame.gear
+        );
+        Ok(())
+    }
+}
+
+/// CAN 总线执行器后端：每个通道各发一帧，数据为 `[channel_id, i16 value 大端, 0, 0, 0, 0]`，
+/// 值按 ±1.0 归一化后映射到 i16 全量程，具体 DBC 由机器侧约定。
+#[cfg(feature = "socketcan")]
+pub struct CanActuatorSink {
+    socket: socketcan::CanSocket,
+    base_id: u32,
+}
+
+#[cfg(feature = "socketcan")]
+impl CanActuatorSink {
+    pub fn new(iface: &str, base_id: u32) -> Result<Self> {
+        let socket = socketcan::CanSocket::open(iface).with_context(|| format!("打开 CAN 接口失败: {}", iface))?;
+        println!("✅ CAN 执行器后端已打开: iface={}, base_id=0x{:03X}", iface, base_id);
+        Ok(Self { socket, base_id })
+    }
+
+    fn send_channel(&self, offset: u32, value: f64) -> Result<()> {
+        let scaled = (value.clamp(-1.0, 1.0) * i16::MAX as f64) as i16;
+        let bytes = scaled.to_be_bytes();
+        let data = [bytes[0], bytes[1], 0, 0, 0, 0, 0, 0];
+        let frame = socketcan::CanFrame::new(self.base_id + offset, &data).context("构造 CAN 帧失败")?;
+        self.socket.write_frame(&frame).context("发送 CAN 帧失败")?;
+        Ok(())
+    }
+
+    /// 档位走独立编码，不能复用 `send_channel` 的 ±1 归一化：gear 是 0..3 的离散值，
+    /// clamp 到 [-1,1] 会让 1(R)/2(N)/3(D) 全部饱和成同一个 CAN 报文，R/D 在总线上无法区分。
+    fn send_gear(&self, offset: u32, gear: u8) -> Result<()> {
+        let data = [gear, 0, 0, 0, 0, 0, 0, 0];
+        let frame = socketcan::CanFrame::new(self.base_id + offset, &data).context("构造 CAN 档位帧失败")?;
+        self.socket.write_frame(&frame).context("发送 CAN 档位帧失败")?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "socketcan")]
+impl ActuatorSink for CanActuatorSink {
+    fn apply(&mut self, frame: &ActuatorFrame) -> Result<()> {
+        self.send_channel(0, frame.rotation)?;
+        self.send_channel(1, frame.throttle)?;
+        self.send_channel(2, frame.brake)?;
+        self.send_channel(3, frame.boom)?;
+        self.send_channel(4, frame.bucket)?;
+        self.send_channel(5, frame.swing)?;
+        self.send_channel(6, frame.stick)?;
+        self.send_channel(7, frame.left_track)?;
+        self.send_channel(8, frame.right_track)?;
+        self.send_gear(9, frame.gear)
+    }
+}
+
+/// 串口/PWM 执行器后端：把每个通道编码为 1000~2000us 的标准舵机脉宽，以
+/// `CH<index>:<us>\n` 的 ASCII 行协议下发给下位机（常见爱好级舵机/电调控制板约定）。
+#[cfg(feature = "serialport")]
+pub struct SerialActuatorSink {
+    port: Box<dyn serialport::SerialPort>,
+}
+
+#[cfg(feature = "serialport")]
+impl SerialActuatorSink {
+    pub fn new(port_name: &str, baud: u32) -> Result<Self> {
+        let port = serialport::new(port_name, baud)
+            .timeout(Duration::from_millis(50))
+            .open()
+            .with_context(|| format!("打开串口失败: {}", port_name))?;
+        println!("✅ 串口执行器后端已打开: port={}, baud={}", port_name, baud);
+        Ok(Self { port })
+    }
+
+    fn write_channel(&mut self, index: u8, normalized: f64) -> Result<()> {
+        let us = 1500.0 + normalized.clamp(-1.0, 1.0) * 500.0;
+        let line = format!("CH{}:{}\n", index, us.round() as u32);
+        self.port.write_all(line.as_bytes()).context("写串口失败")?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialport")]
+impl ActuatorSink for SerialActuatorSink {
+    fn apply(&mut self, frame: &ActuatorFrame) -> Result<()> {
+        self.write_channel(0, frame.rotation)?;
+        self.write_channel(1, frame.throttle)?;
+        self.write_channel(2, frame.brake)?;
+        self.write_channel(3, frame.boom)?;
+        self.write_channel(4, frame.bucket)?;
+        self.write_channel(5, frame.swing)?;
+        self.write_channel(6, frame.stick)?;
+        self.write_channel(7, frame.left_track)?;
+        self.write_channel(8, frame.right_track)?;
+        self.write_channel(9, (frame.gear as f64 - 1.5) / 1.5) // 档位归一化到 ±1 区间后复用同一条协议
+    }
+}
+
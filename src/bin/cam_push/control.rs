@@ -0,0 +1,122 @@
+// control.rs
+// 统一控制状态：可以由 LiveKit DataChannel 或 ROS2 话题任一方驱动，
+// 二者写入同一个 Arc<Mutex<UnifiedControlMessage>>，供后续（如执行器输出）读取。
+
+use anyhow::Result;
+use rclrs::{CreateBasicExecutor, RclrsErrorFilter};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std_msgs::msg::String as RosString;
+
+/// 统一控制消息结构（与 ros2_simple::UnifiedControlMessage 字段保持一致）
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnifiedControlMessage {
+    pub rotation: f64,
+    pub brake: f64,
+    pub throttle: f64,
+    pub gear: String,
+    pub boom: f64,
+    pub bucket: f64,
+    pub left_track: f64,
+    pub right_track: f64,
+    pub swing: f64,
+    pub stick: f64,
+    pub device_type: String,
+    pub timestamp: i64,
+}
+
+impl Default for UnifiedControlMessage {
+    fn default() -> Self {
+        Self {
+            rotation: 0.0,
+            brake: 0.0,
+            throttle: 0.0,
+            gear: "N".to_string(),
+            boom: 0.0,
+            bucket: 0.0,
+            left_track: 0.0,
+            right_track: 0.0,
+            swing: 0.0,
+            stick: 0.0,
+            device_type: "wheel_loader".to_string(),
+            timestamp: 0,
+        }
+    }
+}
+
+/// 将一条 JSON 控制消息合并到现有状态中（gear/analog 增量更新，而非整体替换）
+pub fn merge_control_message(current: &mut UnifiedControlMessage, json_data: &str) -> Result<()> {
+    let json: Value = serde_json::from_str(json_data)?;
+
+    if let Some(t) = json.get("t").and_then(|v| v.as_i64()) {
+        current.timestamp = t;
+    }
+
+    if let Some(msg_type) = json.get("type").and_then(|v| v.as_str()) {
+        match msg_type {
+            "gear" => {
+                if let Some(gear) = json.get("gear").and_then(|v| v.as_str()) {
+                    current.gear = gear.to_string();
+                }
+            }
+            "analog" => {
+                if let Some(v_obj) = json.get("v") {
+                    if let Some(x) = v_obj.get("rotation").and_then(|v| v.as_f64()) { current.rotation = x; }
+                    if let Some(x) = v_obj.get("brake").and_then(|v| v.as_f64()) { current.brake = x; }
+                    if let Some(x) = v_obj.get("throttle").and_then(|v| v.as_f64()) { current.throttle = x; }
+                    if let Some(x) = v_obj.get("boom").and_then(|v| v.as_f64()) { current.boom = x; }
+                    if let Some(x) = v_obj.get("bucket").and_then(|v| v.as_f64()) { current.bucket = x; }
+                    if let Some(x) = v_obj.get("leftTrack").and_then(|v| v.as_f64()) { current.left_track = x; }
+                    if let Some(x) = v_obj.get("rightTrack").and_then(|v| v.as_f64()) { current.right_track = x; }
+                    if let Some(x) = v_obj.get("swing").and_then(|v| v.as_f64()) { current.swing = x; }
+                    if let Some(x) = v_obj.get("stick").and_then(|v| v.as_f64()) { current.stick = x; }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// 启动 ROS2 控制订阅线程（CONTROL_SOURCE=ros 时使用），更新同一个控制状态。
+pub fn start_ros2_control_subscriber(control_state: Arc<Mutex<UnifiedControlMessage>>, topic: String) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut executor = match rclrs::Context::default_from_env() {
+            Ok(ctx) => ctx.create_basic_executor(),
+            Err(e) => {
+                eprintln!("ROS2 Context init failed (cam_push control): {:?}", e);
+                return;
+            }
+        };
+        let node = match executor.create_node("cam_push_control_bridge") {
+            Ok(n) => n,
+            Err(e) => {
+                eprintln!("ROS2 Node create failed (cam_push control): {:?}", e);
+                return;
+            }
+        };
+
+        let _subscription = match node.create_subscription::<RosString, _>(&topic, move |msg: RosString| {
+            let mut state = control_state.lock().unwrap();
+            if let Err(e) = merge_control_message(&mut state, &msg.data) {
+                eprintln!("⚠️  ROS2 控制消息解析失败: {:?}", e);
+            }
+        }) {
+            Ok(s) => {
+                println!("✅ cam_push ROS2 控制订阅已创建: topic='{}'", topic);
+                s
+            }
+            Err(e) => {
+                eprintln!("ROS2 Subscription create failed (cam_push control): {:?}", e);
+                return;
+            }
+        };
+
+        let errs = executor.spin(rclrs::SpinOptions::default());
+        if let Err(e) = errs.first_error() {
+            eprintln!("ROS2 spin failed (cam_push control): {:?}", e);
+        }
+    })
+}
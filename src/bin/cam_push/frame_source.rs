@@ -0,0 +1,338 @@
+// frame_source.rs
+// 统一的帧来源抽象：本地 V4L2 设备与网络 RTSP 相机都实现 `FrameSource`，
+// 产出同样的 I420 三平面数据，使 main 循环（打时间戳、预览、LiveKit 推流）与来源无关。
+
+use anyhow::{Context, Result};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use v4l::io::mmap::Stream as MmapStream;
+use v4l::io::traits::CaptureStream;
+
+use crate::simd_convert::{rgb_to_i420_planes_fast, yuyv_to_i420_planes_fast};
+
+// V4L2 设备打开/格式协商/mmap 采集流建立逻辑与 `ros2_simple.rs` 的视频发布者共用，
+// 提到 `src/v4l2_capture.rs` 一处维护，这里按相对路径引入同一份源文件。
+#[path = "../../v4l2_capture.rs"]
+mod v4l2_capture;
+
+/// 阻塞拉取下一帧 I420 数据 `(Y, U, V, width, height)`。
+pub trait FrameSource: Send {
+    fn next_i420(&mut self) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>, u32, u32)>;
+}
+
+/// YUYV（打包 4:2:2）转 I420，按 2x2 block 采样色度。
+pub fn yuyv_to_i420_planes(src: &[u8], width: usize, height: usize) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    if src.len() < width * height * 2 {
+        anyhow::bail!("YUYV 帧大小不足");
+    }
+    let mut y = vec![0u8; width * height];
+    let mut u = vec![0u8; (width / 2) * (height / 2)];
+    let mut v = vec![0u8; (width / 2) * (height / 2)];
+
+    for j in (0..height).step_by(2) {
+        let row0_base = j * width * 2;
+        let row1_base = (j + 1) * width * 2;
+        for i in (0..width).step_by(2) {
+            let idx0 = row0_base + i * 2;
+            let idx1 = row1_base + i * 2;
+
+            let y00 = src[idx0];
+            let u0 = src[idx0 + 1];
+            let y01 = src[idx0 + 2];
+            let v0 = src[idx0 + 3];
+            let y10 = src[idx1];
+            let u1 = src[idx1 + 1];
+            let y11 = src[idx1 + 2];
+            let v1 = src[idx1 + 3];
+
+            y[j * width + i] = y00;
+            y[j * width + i + 1] = y01;
+            y[(j + 1) * width + i] = y10;
+            y[(j + 1) * width + i + 1] = y11;
+
+            let u_avg = ((u0 as u16 + u1 as u16) / 2) as u8;
+            let v_avg = ((v0 as u16 + v1 as u16) / 2) as u8;
+            let uvi = (j / 2) * (width / 2) + (i / 2);
+            u[uvi] = u_avg;
+            v[uvi] = v_avg;
+        }
+    }
+    Ok((y, u, v))
+}
+
+/// 已解码的 RGB8 数据转 I420（BT.601 全范围公式，逐 2x2 block 平均色度）。标量参考实现，
+/// 供不支持 SIMD 的架构或 `simd_convert` 中的快路径回退使用。
+pub fn rgb_to_i420_planes(rgb: &[u8], width: usize, height: usize) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    if rgb.len() != width * height * 3 {
+        anyhow::bail!("RGB 数据尺寸不匹配: got={}, expected={}", rgb.len(), width * height * 3);
+    }
+
+    let mut y = vec![0u8; width * height];
+    let mut u = vec![0u8; (width / 2) * (height / 2)];
+    let mut v = vec![0u8; (width / 2) * (height / 2)];
+    for j in (0..height).step_by(2) {
+        for i in (0..width).step_by(2) {
+            let mut u_acc: i32 = 0;
+            let mut v_acc: i32 = 0;
+            for dy in 0..2 {
+                for dx in 0..2 {
+                    let x = i + dx;
+                    let yj = j + dy;
+                    let idx = (yj * width + x) * 3;
+                    let r = rgb[idx] as f32;
+                    let g = rgb[idx + 1] as f32;
+                    let b = rgb[idx + 2] as f32;
+                    let y_val = (0.257 * r + 0.504 * g + 0.098 * b + 16.0).round() as i32;
+                    y[yj * width + x] = y_val.clamp(0, 255) as u8;
+                    let u_val = (-0.148 * r - 0.291 * g + 0.439 * b + 128.0).round() as i32;
+                    let v_val = (0.439 * r - 0.368 * g - 0.071 * b + 128.0).round() as i32;
+                    u_acc += u_val;
+                    v_acc += v_val;
+                }
+            }
+            let uvi = (j / 2) * (width / 2) + (i / 2);
+            u[uvi] = (u_acc / 4).clamp(0, 255) as u8;
+            v[uvi] = (v_acc / 4).clamp(0, 255) as u8;
+        }
+    }
+    Ok((y, u, v))
+}
+
+/// MJPG 解码为 RGB 后转 I420。解码本身默认用软件 `jpeg_decoder`；设置
+/// `CAM_MJPG_DECODER=vaapi` 时改走 GPU/VAAPI 硬件解码管线（需要平台支持 VA-API）。
+pub fn mjpg_to_i420_planes(src: &[u8], width: usize, height: usize) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let decoder = std::env::var("CAM_MJPG_DECODER").unwrap_or_else(|_| "software".to_string());
+    let rgb = if decoder.eq_ignore_ascii_case("vaapi") {
+        decode_mjpg_vaapi(src, width, height)?
+    } else {
+        jpeg_decoder::Decoder::new(src).decode().context("MJPG 解码失败")?
+    };
+    if rgb.len() != width * height * 3 {
+        anyhow::bail!("MJPG 解码后尺寸不匹配: got={}, expected={}", rgb.len(), width * height * 3);
+    }
+    rgb_to_i420_planes_fast(&rgb, width, height)
+}
+
+/// 用一次性 GStreamer 管线把单帧 MJPG 通过 VA-API 硬件解码为 RGB，绕开软件 JPEG 解码的 CPU 开销。
+fn decode_mjpg_vaapi(src: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+    gst::init().context("GStreamer 初始化失败")?;
+
+    let pipeline_str = "appsrc name=src is-live=false format=time ! jpegparse ! vaapijpegdec ! videoconvert ! video/x-raw,format=RGB ! appsink name=sink sync=false";
+    let pipeline = gst::parse_launch(pipeline_str)?
+        .downcast::<gst::Pipeline>()
+        .map_err(|_| anyhow::anyhow!("解析后的元素不是 Pipeline"))?;
+
+    let appsrc = pipeline
+        .by_name("src")
+        .context("未找到 appsrc 元素")?
+        .downcast::<gst_app::AppSrc>()
+        .map_err(|_| anyhow::anyhow!("src 元素不是 AppSrc"))?;
+    let appsink = pipeline
+        .by_name("sink")
+        .context("未找到 appsink 元素")?
+        .downcast::<gst_app::AppSink>()
+        .map_err(|_| anyhow::anyhow!("sink 元素不是 AppSink"))?;
+
+    pipeline.set_state(gst::State::Playing).context("VA-API 解码管线启动失败")?;
+
+    let buffer = gst::Buffer::from_mut_slice(src.to_vec());
+    appsrc.push_buffer(buffer).map_err(|e| anyhow::anyhow!("推送 MJPG buffer 失败: {:?}", e))?;
+    let _ = appsrc.end_of_stream();
+
+    let sample = appsink.pull_sample().context("拉取 VA-API 解码结果失败")?;
+    let buf = sample.buffer().context("VA-API sample 无 buffer")?;
+    let map = buf.map_readable().context("映射 VA-API 解码结果失败")?;
+    anyhow::ensure!(map.len() >= width * height * 3, "VA-API 解码结果尺寸不足: got={}, expected={}", map.len(), width * height * 3);
+    let rgb = map[0..width * height * 3].to_vec();
+
+    let _ = pipeline.set_state(gst::State::Null);
+    Ok(rgb)
+}
+
+/// NV12（半平面，Y 全分辨率 + 交织 UV 半分辨率）转 I420：拆开交织的 UV 分量即可，无需重采样。
+pub fn nv12_to_i420_planes(src: &[u8], width: usize, height: usize) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let y_size = width * height;
+    let uv_plane = (width / 2) * (height / 2);
+    anyhow::ensure!(src.len() >= y_size + 2 * uv_plane, "NV12 帧大小不足: got={}, expected={}", src.len(), y_size + 2 * uv_plane);
+
+    let y = src[0..y_size].to_vec();
+    let uv = &src[y_size..y_size + 2 * uv_plane];
+    let mut u = vec![0u8; uv_plane];
+    let mut v = vec![0u8; uv_plane];
+    for i in 0..uv_plane {
+        u[i] = uv[2 * i];
+        v[i] = uv[2 * i + 1];
+    }
+    Ok((y, u, v))
+}
+
+/// 本地 V4L2 采集设备帧源，沿用既有的 YUYV/MJPG 解码路径。
+pub struct V4l2Source {
+    stream: MmapStream<'static>,
+    fourcc: v4l::FourCC,
+    width: u32,
+    height: u32,
+}
+
+impl V4l2Source {
+    pub fn new(cam_index: usize, width: u32, height: u32, fourcc_str: &str) -> Result<Self> {
+        let (stream, width, height, fourcc) = v4l2_capture::open_v4l2_stream(cam_index, width, height, fourcc_str)?;
+
+        Ok(Self { stream, fourcc, width, height })
+    }
+}
+
+impl FrameSource for V4l2Source {
+    fn next_i420(&mut self) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>, u32, u32)> {
+        let (buf, _meta) = self.stream.next().context("读取采集帧失败")?;
+        let w = self.width as usize;
+        let h = self.height as usize;
+
+        let (y, u, v) = if self.fourcc == v4l::FourCC::new(b"YUYV") {
+            yuyv_to_i420_planes_fast(buf, w, h)?
+        } else if self.fourcc == v4l::FourCC::new(b"MJPG") {
+            mjpg_to_i420_planes(buf, w, h)?
+        } else if self.fourcc == v4l::FourCC::new(b"NV12") {
+            nv12_to_i420_planes(buf, w, h)?
+        } else {
+            anyhow::bail!("不支持的 V4L2 像素格式: {:?}", self.fourcc);
+        };
+
+        Ok((y, u, v, self.width, self.height))
+    }
+}
+
+/// 初始重连退避时长
+const RECONNECT_BACKOFF_MIN: std::time::Duration = std::time::Duration::from_millis(500);
+/// 最大重连退避时长
+const RECONNECT_BACKOFF_MAX: std::time::Duration = std::time::Duration::from_secs(10);
+
+type I420Frame = (Vec<u8>, Vec<u8>, Vec<u8>, u32, u32);
+
+/// 网络 RTSP 相机帧源：拉流解码为 H.264/H.265，经 `avdec_h26x` + `videoconvert` 落到 I420。
+///
+/// 管道的建立/拉流/断线重连全部跑在独立的后台线程上（与 `rtsp_source.rs` 的推流版本同一套
+/// 指数退避策略），解码出的帧经 `std::sync::mpsc` 送回来。`main.rs` 的主循环在同一个
+/// `tokio::select!` 里还要处理 DataChannel 控制消息，如果重连时的 `sleep` 跑在调用
+/// `next_i420()` 的那个异步任务里，会把控制消息处理一起卡住最多一个退避周期；放到独立线程上
+/// 重连时，`next_i420()` 只需短超时地等一帧，不会阻塞控制平面。
+pub struct RtspSource {
+    url: String,
+    rx: std::sync::mpsc::Receiver<I420Frame>,
+    stop_flag: Arc<AtomicBool>,
+    _handle: std::thread::JoinHandle<()>,
+}
+
+impl RtspSource {
+    pub fn new(url: &str) -> Result<Self> {
+        gst::init().context("GStreamer 初始化失败")?;
+
+        let (tx, rx) = std::sync::mpsc::sync_channel::<I420Frame>(2);
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop_flag.clone();
+        let url_owned = url.to_string();
+        let handle = std::thread::spawn(move || {
+            run_rtsp_with_reconnect(&url_owned, &tx, &thread_stop);
+        });
+
+        Ok(Self { url: url.to_string(), rx, stop_flag, _handle: handle })
+    }
+}
+
+impl FrameSource for RtspSource {
+    fn next_i420(&mut self) -> Result<I420Frame> {
+        self.rx
+            .recv_timeout(std::time::Duration::from_millis(200))
+            .with_context(|| format!("RTSP 帧源暂无新帧（可能正在重连）: {}", self.url))
+    }
+}
+
+impl Drop for RtspSource {
+    fn drop(&mut self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+    }
+}
+
+fn build_rtsp_pipeline(url: &str) -> Result<(gst::Pipeline, gst_app::AppSink)> {
+    let codec = std::env::var("CAM_RTSP_CODEC").unwrap_or_else(|_| "h264".to_string());
+    let (depay, decoder) = match codec.to_lowercase().as_str() {
+        "h265" | "hevc" => ("rtph265depay", "avdec_h265"),
+        _ => ("rtph264depay", "avdec_h264"),
+    };
+
+    let pipeline_str = format!(
+        "rtspsrc location={} latency=200 ! {} ! {} ! videoconvert ! video/x-raw,format=I420 ! appsink name=sink sync=false max-buffers=2 drop=true",
+        url, depay, decoder
+    );
+
+    let pipeline = gst::parse_launch(&pipeline_str)?
+        .downcast::<gst::Pipeline>()
+        .map_err(|_| anyhow::anyhow!("解析后的元素不是 Pipeline"))?;
+    let appsink = pipeline
+        .by_name("sink")
+        .context("未找到 appsink 元素")?
+        .downcast::<gst_app::AppSink>()
+        .map_err(|_| anyhow::anyhow!("sink 元素不是 AppSink"))?;
+
+    pipeline.set_state(gst::State::Playing).context("RTSP 管道启动失败")?;
+    println!("✅ RTSP 帧源已启动: {}", url);
+
+    Ok((pipeline, appsink))
+}
+
+/// 后台线程主体：建流 -> 拉帧直到断流/出错 -> 按指数退避睡眠 -> 重新建流，直到 `stop_flag` 置位。
+fn run_rtsp_with_reconnect(url: &str, tx: &std::sync::mpsc::SyncSender<I420Frame>, stop_flag: &Arc<AtomicBool>) {
+    let mut backoff = RECONNECT_BACKOFF_MIN;
+    while !stop_flag.load(Ordering::Relaxed) {
+        match pull_until_error(url, tx, stop_flag) {
+            Ok(()) => backoff = RECONNECT_BACKOFF_MIN,
+            Err(e) => eprintln!("⚠️  RTSP 拉流异常，{:?} 后重连: url={} err={:?}", backoff, url, e),
+        }
+
+        if stop_flag.load(Ordering::Relaxed) {
+            return;
+        }
+        std::thread::sleep(backoff);
+        backoff = std::cmp::min(backoff * 2, RECONNECT_BACKOFF_MAX);
+    }
+}
+
+/// 建一条管道，循环拉帧直到 EOS/错误/`stop_flag` 置位；返回值仅表示本次管道生命周期是否正常结束。
+fn pull_until_error(url: &str, tx: &std::sync::mpsc::SyncSender<I420Frame>, stop_flag: &Arc<AtomicBool>) -> Result<()> {
+    let (pipeline, appsink) = build_rtsp_pipeline(url)?;
+
+    let result = (|| -> Result<()> {
+        while !stop_flag.load(Ordering::Relaxed) {
+            let sample = match appsink.try_pull_sample(gst::ClockTime::from_mseconds(500)) {
+                Some(s) => s,
+                None => continue, // 拉取超时，检查 stop_flag 后继续等下一帧
+            };
+            let buffer = sample.buffer().context("RTSP sample 无 buffer")?;
+            let caps = sample.caps().context("RTSP sample 无 caps")?;
+            let info = gstreamer_video::VideoInfo::from_caps(caps).map_err(|_| anyhow::anyhow!("解析 VideoInfo 失败"))?;
+            let width = info.width();
+            let height = info.height();
+
+            let map = buffer.map_readable().context("映射 RTSP buffer 失败")?;
+            let y_size = (width as usize) * (height as usize);
+            let uv_plane = y_size / 4;
+            let expected = y_size + 2 * uv_plane;
+            anyhow::ensure!(map.len() >= expected, "RTSP I420 帧长度不足: got={}, expected={}", map.len(), expected);
+
+            let y = map[0..y_size].to_vec();
+            let u = map[y_size..y_size + uv_plane].to_vec();
+            let v = map[y_size + uv_plane..expected].to_vec();
+            drop(map);
+
+            // 通道满说明消费端还没取走上一帧，直接丢弃本帧（与 appsink 的 drop=true 语义一致）
+            let _ = tx.try_send((y, u, v, width, height));
+        }
+        Ok(())
+    })();
+
+    let _ = pipeline.set_state(gst::State::Null);
+    result
+}
@@ -5,65 +5,25 @@ use livekit::prelude::*;
 use livekit::webrtc::video_frame::{I420Buffer, VideoFrame, VideoRotation};
 use livekit::webrtc::video_source::native::NativeVideoSource;
 use livekit::webrtc::video_source::RtcVideoSource;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::time::{self, Duration, Instant};
 use chrono::Local;
-use v4l::buffer::Type;
-use v4l::io::mmap::Stream as MmapStream;
-use v4l::io::traits::CaptureStream;
-use v4l::prelude::*;
-use v4l::video::Capture as _;
 use sdl2::{pixels::PixelFormatEnum, rect::Rect};
 
-fn yuyv_to_i420_planes(src: &[u8], width: usize, height: usize) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
-    // YUYV 4:2:2 每 2 像素 4 字节: Y0 U Y1 V
-    if src.len() < width * height * 2 {
-        anyhow::bail!("YUYV 帧大小不足");
-    }
-    let mut y = vec![0u8; width * height];
-    let mut u = vec![0u8; (width / 2) * (height / 2)];
-    let mut v = vec![0u8; (width / 2) * (height / 2)];
-
-    // 对 2x2 block 采样：
-    // 行 j, j+1；列 i(偶), i+1(奇)
-    for j in (0..height).step_by(2) {
-        let row0_base = j * width * 2;
-        let row1_base = (j + 1) * width * 2;
-        for i in (0..width).step_by(2) {
-            let idx0 = row0_base + i * 2; // 行 j，列 i 的起始（偶列）
-            let idx1 = row1_base + i * 2; // 行 j+1，列 i 的起始（偶列）
+mod simd_convert;
 
-            // 行 j 的两个像素 Y0,Y1 和共享的 U,V
-            let y00 = src[idx0];
-            let u0 = src[idx0 + 1];
-            let y01 = src[idx0 + 2];
-            let v0 = src[idx0 + 3];
-            // 行 j+1 的两个像素 Y0,Y1 和共享的 U,V（同列）
-            let y10 = src[idx1];
-            let u1 = src[idx1 + 1];
-            let y11 = src[idx1 + 2];
-            let v1 = src[idx1 + 3];
+mod frame_source;
+use frame_source::{FrameSource, RtspSource, V4l2Source};
 
-            // 写入 Y 平面
-            y[j * width + i] = y00;
-            y[j * width + i + 1] = y01;
-            y[(j + 1) * width + i] = y10;
-            y[(j + 1) * width + i + 1] = y11;
+mod rtsp_server;
+use rtsp_server::start_rtsp_server;
 
-            // 下采样平均得到 U/V（2 行同列平均）
-            let u_avg = ((u0 as u16 + u1 as u16) / 2) as u8;
-            let v_avg = ((v0 as u16 + v1 as u16) / 2) as u8;
-            let uvi = (j / 2) * (width / 2) + (i / 2);
-            u[uvi] = u_avg;
-            v[uvi] = v_avg;
-        }
-    }
-    Ok((y, u, v))
-}
+mod control;
+use control::{merge_control_message, start_ros2_control_subscriber, UnifiedControlMessage};
 
-// 5x7 简易字体（每个字符 5 列、7 行，bit1 表示填充）
-const FONT_5X7: [[u8; 7]; 12] = [
-    // '0'..'9', ':', '.'
+// 5x7 简易字体（每个字符 5 列、7 行，bit1 表示填充）：数字、':'、'.'、空格、A-Z、'-'、'/'
+const FONT_5X7: [[u8; 7]; 41] = [
+    // '0'..'9'
     [0b01110,0b10001,0b10011,0b10101,0b11001,0b10001,0b01110], // 0
     [0b00100,0b01100,0b00100,0b00100,0b00100,0b00100,0b01110], // 1
     [0b01110,0b10001,0b00001,0b00010,0b00100,0b01000,0b11111], // 2
@@ -74,8 +34,41 @@ const FONT_5X7: [[u8; 7]; 12] = [
     [0b11111,0b00001,0b00010,0b00100,0b01000,0b01000,0b01000], // 7
     [0b01110,0b10001,0b10001,0b01110,0b10001,0b10001,0b01110], // 8
     [0b01110,0b10001,0b10001,0b01111,0b00001,0b00010,0b01100], // 9
+    // ':', '.'
     [0b00000,0b00100,0b00100,0b00000,0b00100,0b00100,0b00000], // ':'
     [0b00000,0b00000,0b00000,0b00000,0b00000,0b00100,0b00000], // '.'
+    // 空格
+    [0b00000,0b00000,0b00000,0b00000,0b00000,0b00000,0b00000], // ' '
+    // 'A'..'Z'
+    [0b01110,0b10001,0b10001,0b11111,0b10001,0b10001,0b10001], // A
+    [0b11110,0b10001,0b10001,0b11110,0b10001,0b10001,0b11110], // B
+    [0b01111,0b10000,0b10000,0b10000,0b10000,0b10000,0b01111], // C
+    [0b11110,0b10001,0b10001,0b10001,0b10001,0b10001,0b11110], // D
+    [0b11111,0b10000,0b10000,0b11110,0b10000,0b10000,0b11111], // E
+    [0b11111,0b10000,0b10000,0b11110,0b10000,0b10000,0b10000], // F
+    [0b01111,0b10000,0b10000,0b10111,0b10001,0b10001,0b01111], // G
+    [0b10001,0b10001,0b10001,0b11111,0b10001,0b10001,0b10001], // H
+    [0b01110,0b00100,0b00100,0b00100,0b00100,0b00100,0b01110], // I
+    [0b00111,0b00010,0b00010,0b00010,0b00010,0b10010,0b01100], // J
+    [0b10001,0b10010,0b10100,0b11000,0b10100,0b10010,0b10001], // K
+    [0b10000,0b10000,0b10000,0b10000,0b10000,0b10000,0b11111], // L
+    [0b10001,0b11011,0b10101,0b10101,0b10001,0b10001,0b10001], // M
+    [0b10001,0b11001,0b10101,0b10011,0b10001,0b10001,0b10001], // N
+    [0b01110,0b10001,0b10001,0b10001,0b10001,0b10001,0b01110], // O
+    [0b11110,0b10001,0b10001,0b11110,0b10000,0b10000,0b10000], // P
+    [0b01110,0b10001,0b10001,0b10001,0b10101,0b10010,0b01101], // Q
+    [0b11110,0b10001,0b10001,0b11110,0b10100,0b10010,0b10001], // R
+    [0b01111,0b10000,0b10000,0b01110,0b00001,0b00001,0b11110], // S
+    [0b11111,0b00100,0b00100,0b00100,0b00100,0b00100,0b00100], // T
+    [0b10001,0b10001,0b10001,0b10001,0b10001,0b10001,0b01110], // U
+    [0b10001,0b10001,0b10001,0b10001,0b10001,0b01010,0b00100], // V
+    [0b10001,0b10001,0b10001,0b10101,0b10101,0b10101,0b01010], // W
+    [0b10001,0b10001,0b01010,0b00100,0b01010,0b10001,0b10001], // X
+    [0b10001,0b10001,0b01010,0b00100,0b00100,0b00100,0b00100], // Y
+    [0b11111,0b00001,0b00010,0b00100,0b01000,0b10000,0b11111], // Z
+    // '-', '/'
+    [0b00000,0b00000,0b00000,0b11111,0b00000,0b00000,0b00000], // '-'
+    [0b00001,0b00001,0b00010,0b00100,0b01000,0b10000,0b10000], // '/'
 ];
 
 fn glyph_index(ch: char) -> Option<usize> {
@@ -83,6 +76,10 @@ fn glyph_index(ch: char) -> Option<usize> {
         '0'..='9' => Some((ch as u8 - b'0') as usize),
         ':' => Some(10),
         '.' => Some(11),
+        ' ' => Some(12),
+        'A'..='Z' => Some(13 + (ch as u8 - b'A') as usize),
+        '-' => Some(39),
+        '/' => Some(40),
         _ => None,
     }
 }
@@ -139,7 +136,7 @@ async fn main() -> Result<()> {
         .context("请设置 LIVEKIT_TOKEN 或 LIVEKIT_STATIC_TOKEN 为可用的访问令牌")?;
 
     // 连接房间
-    let (room, mut _events) = Room::connect(&lk_url, &lk_token, RoomOptions::default())
+    let (room, mut room_events) = Room::connect(&lk_url, &lk_token, RoomOptions::default())
         .await
         .context("连接 LiveKit 失败")?;
 
@@ -165,28 +162,51 @@ async fn main() -> Result<()> {
         .await
         .context("发布视频轨道失败")?;
 
-    // 打开摄像头（/dev/video0）
-    let cam_index: usize = std::env::var("CAM_INDEX").ok().and_then(|s| s.parse().ok()).unwrap_or(0);
-    let mut dev = Device::new(cam_index).context("打开摄像头失败")?;
-
-    // 配置分辨率/帧率与像素格式（默认 YUYV，可通过 CAM_FOURCC 覆盖）
+    // 分辨率/帧率（V4L2 与 RTSP 共用同一套环境变量约定 CAM_WIDTH/CAM_HEIGHT/CAM_FPS）
     let width: u32 = std::env::var("CAM_WIDTH").ok().and_then(|s| s.parse().ok()).unwrap_or(1280);
     let height: u32 = std::env::var("CAM_HEIGHT").ok().and_then(|s| s.parse().ok()).unwrap_or(720);
     let fps: u32 = std::env::var("CAM_FPS").ok().and_then(|s| s.parse().ok()).unwrap_or(20);
-    let fourcc_str = std::env::var("CAM_FOURCC").unwrap_or_else(|_| "YUYV".to_string());
-    let mut fourcc_bytes = [0u8; 4];
-    for (i, b) in fourcc_str.bytes().take(4).enumerate() { fourcc_bytes[i] = b; }
 
-    let mut fmt = dev.format()?;
-    fmt.width = width;
-    fmt.height = height;
-    fmt.fourcc = v4l::FourCC::new(&fourcc_bytes);
-    let fmt = dev.set_format(&fmt).context("设置摄像头格式失败")?;
+    // 帧来源：默认本地 V4L2 设备，CAM_SOURCE=rtsp 时改为拉取网络 RTSP 相机
+    let cam_source = std::env::var("CAM_SOURCE").unwrap_or_else(|_| "v4l2".to_string());
+    let mut source_backend: Box<dyn FrameSource> = match cam_source.as_str() {
+        "rtsp" => {
+            let rtsp_url = std::env::var("CAM_RTSP_URL").context("CAM_SOURCE=rtsp 需要设置 CAM_RTSP_URL")?;
+            Box::new(RtspSource::new(&rtsp_url)?)
+        }
+        _ => {
+            let cam_index: usize = std::env::var("CAM_INDEX").ok().and_then(|s| s.parse().ok()).unwrap_or(0);
+            let fourcc_str = std::env::var("CAM_FOURCC").unwrap_or_else(|_| "YUYV".to_string());
+            Box::new(V4l2Source::new(cam_index, width, height, &fourcc_str)?)
+        }
+    };
 
-    let _params = dev.params()?; // 某些平台无法程序化设置 fps，这里沿用当前设置
+    // 可选内置 RTSP 服务器：设置 RTSP_SERVER_PORT 后启用，复用上面同一路采集帧
+    let rtsp_server_handle = match std::env::var("RTSP_SERVER_PORT") {
+        Ok(port_str) => {
+            let port: u16 = port_str.parse().unwrap_or(8554);
+            let mount_path = std::env::var("RTSP_SERVER_MOUNT").unwrap_or_else(|_| "/test".to_string());
+            let user = std::env::var("RTSP_SERVER_USER").ok();
+            let pass = std::env::var("RTSP_SERVER_PASS").ok();
+            match start_rtsp_server(port, &mount_path, user, pass) {
+                Ok(handle) => Some(handle),
+                Err(e) => {
+                    eprintln!("⚠️  内置 RTSP 服务器启动失败: {:?}", e);
+                    None
+                }
+            }
+        }
+        Err(_) => None,
+    };
 
-    // 内存映射采集流
-    let mut stream = MmapStream::with_buffers(&dev, Type::VideoCapture, 4).context("创建采集流失败")?;
+    // 统一控制状态：默认通过 LiveKit DataChannel 接收（CONTROL_SOURCE=datachannel），
+    // 也可设置 CONTROL_SOURCE=ros 改为从 ROS2 话题订阅，二者写入同一份状态。
+    let control_state: Arc<Mutex<UnifiedControlMessage>> = Arc::new(Mutex::new(UnifiedControlMessage::default()));
+    let control_source = std::env::var("CONTROL_SOURCE").unwrap_or_else(|_| "datachannel".to_string());
+    if control_source == "ros" {
+        let topic = std::env::var("CONTROL_ROS_TOPIC").unwrap_or_else(|_| "/control/unified".to_string());
+        let _ros_control_handle = start_ros2_control_subscriber(control_state.clone(), topic);
+    }
 
     // 推流循环
     let frame_interval = Duration::from_secs_f64(1.0 / (fps as f64).max(1.0));
@@ -223,58 +243,68 @@ async fn main() -> Result<()> {
         }
     }
 
-    loop {
-        ticker.tick().await;
-        let (buf, _meta) = match stream.next() {
-            Ok(f) => f,
-            Err(_) => continue,
-        };
+    // 采集/发布 FPS 指数滑动平均与丢帧计数，用于屏幕叠加层健康度展示
+    let mut capture_last_instant: Option<Instant> = None;
+    let mut capture_fps_ema: f64 = 0.0;
+    let mut publish_last_instant: Option<Instant> = None;
+    let mut publish_fps_ema: f64 = 0.0;
+    let mut drop_count: u64 = 0;
+    const FPS_EMA_ALPHA: f64 = 0.2;
 
-        // 根据实际 fourcc 处理: 支持 YUYV 和 MJPG
-        let (mut y, mut u, mut v) = if fmt.fourcc == v4l::FourCC::new(b"YUYV") {
-            yuyv_to_i420_planes(buf, fmt.width as usize, fmt.height as usize)?
-        } else if fmt.fourcc == v4l::FourCC::new(b"MJPG") {
-            // MJPG: 解码 JPEG 为 RGB，再转换到 I420
-            match jpeg_decoder::Decoder::new(buf).decode() {
-                Ok(rgb) => {
-                    let w_us = fmt.width as usize;
-                    let h_us = fmt.height as usize;
-                    if rgb.len() != w_us * h_us * 3 { continue; }
-                    // RGB -> I420
-                    let mut y = vec![0u8; w_us * h_us];
-                    let mut u = vec![0u8; (w_us/2) * (h_us/2)];
-                    let mut v = vec![0u8; (w_us/2) * (h_us/2)];
-                    for j in (0..h_us).step_by(2) {
-                        for i in (0..w_us).step_by(2) {
-                            let mut u_acc: i32 = 0;
-                            let mut v_acc: i32 = 0;
-                            for dy in 0..2 { for dx in 0..2 {
-                                let x = i + dx; let yj = j + dy;
-                                let idx = (yj*w_us + x) * 3;
-                                let r = rgb[idx] as f32;
-                                let g = rgb[idx+1] as f32;
-                                let b = rgb[idx+2] as f32;
-                                let y_val = (0.257*r + 0.504*g + 0.098*b + 16.0).round() as i32;
-                                y[yj*w_us + x] = y_val.clamp(0,255) as u8;
-                                let u_val = (-0.148*r - 0.291*g + 0.439*b + 128.0).round() as i32;
-                                let v_val = (0.439*r - 0.368*g - 0.071*b + 128.0).round() as i32;
-                                u_acc += u_val; v_acc += v_val;
-                            }}
-                            let uvi = (j/2)*(w_us/2) + (i/2);
-                            u[uvi] = (u_acc/4).clamp(0,255) as u8;
-                            v[uvi] = (v_acc/4).clamp(0,255) as u8;
+    fn update_fps_ema(last: &mut Option<Instant>, ema: &mut f64, now: Instant) {
+        if let Some(prev) = *last {
+            let dt = now.duration_since(prev).as_secs_f64();
+            if dt > 0.0 {
+                let inst_fps = 1.0 / dt;
+                *ema = if *ema <= 0.0 { inst_fps } else { (1.0 - FPS_EMA_ALPHA) * *ema + FPS_EMA_ALPHA * inst_fps };
+            }
+        }
+        *last = Some(now);
+    }
+
+    // 单调递增的 PTS 基准：用同一个起点 Instant 与对应的 UNIX_EPOCH 墙钟时间换算，
+    // 即便某次 tick/帧被跳过，时间戳仍严格递增，避免出现 ts_us≈0 的问题。
+    let start_instant = Instant::now();
+    let start_epoch_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+
+    loop {
+        tokio::select! {
+            // LiveKit DataChannel 控制消息（CONTROL_SOURCE=datachannel 时生效）
+            Some(event) = room_events.recv() => {
+                if control_source == "datachannel" {
+                    if let RoomEvent::DataReceived { payload, .. } = event {
+                        if let Ok(text) = std::str::from_utf8(&payload) {
+                            let mut state = control_state.lock().unwrap();
+                            if let Err(e) = merge_control_message(&mut state, text) {
+                                eprintln!("⚠️  DataChannel 控制消息解析失败: {:?}", e);
+                            }
                         }
                     }
-                    (y,u,v)
                 }
-                Err(_) => { continue }
+                continue;
+            }
+            _ = ticker.tick() => {}
+        }
+
+        let (mut y, mut u, mut v, w, h) = match source_backend.next_i420() {
+            Ok(frame) => frame,
+            Err(e) => {
+                eprintln!("⚠️  读取帧失败，跳过: {:?}", e);
+                drop_count += 1;
+                continue;
             }
-        } else {
-            // 其它格式暂不支持
-            continue;
         };
-        let w = fmt.width;
-        let h = fmt.height;
+        let capture_instant = Instant::now();
+        update_fps_ema(&mut capture_last_instant, &mut capture_fps_ema, capture_instant);
+
+        // 本帧的单调 PTS 与墙钟采集时间（用于与控制消息时间戳对齐计算 glass-to-glass 延迟）
+        let elapsed_us = capture_instant.duration_since(start_instant).as_micros() as i64;
+        let capture_wall_ms = start_epoch_ms + capture_instant.duration_since(start_instant).as_millis() as i64;
+        let control_ts_ms = control_state.lock().unwrap().timestamp;
+        let latency_ms = if control_ts_ms > 0 { capture_wall_ms - control_ts_ms } else { 0 };
 
         // 叠加时间戳文本（本地时间）
         let now = Local::now();
@@ -296,6 +326,22 @@ async fn main() -> Result<()> {
         }
         draw_text_i420(&mut y, &mut u, &mut v, w as usize, h as usize, x, ytop, scale, &ts_text);
 
+        // 第二行：采集/发布 FPS、丢帧计数与相对最近控制消息的 glass-to-glass 延迟。
+        // 紧贴时间戳行放在远离画面边缘的一侧：底部锚定(bl/br)时画在时间戳上方，
+        // 否则（顶部锚定/默认）画在下方，避免底部锚定时被裁到画面外。
+        let stats_text = format!("FPS {:.1}/{:.1} DROP {} LAT {}", capture_fps_ema, publish_fps_ema, drop_count, latency_ms);
+        let stats_ytop = match pos.as_str() {
+            "bl" | "br" => ytop.saturating_sub(text_px_h + 4),
+            _ => ytop + text_px_h + 4,
+        };
+        draw_text_i420(&mut y, &mut u, &mut v, w as usize, h as usize, x, stats_ytop, scale, &stats_text);
+
+        // 叠加时间戳后的同一帧也喂给内置 RTSP 服务器（如已启用）
+        if let Some(handle) = &rtsp_server_handle {
+            let ts_us = Local::now().timestamp_micros();
+            handle.push_i420(&y, &u, &v, w, h, ts_us);
+        }
+
         // 本地预览：使用 SDL2 IYUV 纹理（与 I420 顺序一致）
         if let Some(canvas) = &mut canvas_opt {
             let y_pitch = w as usize;
@@ -322,11 +368,12 @@ async fn main() -> Result<()> {
             u_dst.copy_from_slice(&u);
             v_dst.copy_from_slice(&v);
         } else {
+            drop_count += 1;
             continue;
         }
 
-        let ts_us = Instant::now();
-        let frame = VideoFrame { rotation: VideoRotation::VideoRotation0, timestamp_us: ts_us.elapsed().as_micros() as i64, buffer };
+        update_fps_ema(&mut publish_last_instant, &mut publish_fps_ema, Instant::now());
+        let frame = VideoFrame { rotation: VideoRotation::VideoRotation0, timestamp_us: elapsed_us, buffer };
         if let RtcVideoSource::Native(native) = &*source {
             native.capture_frame(&frame);
         }
@@ -0,0 +1,108 @@
+// rtsp_server.rs
+// 内置轻量 RTSP 服务器：在发布到 LiveKit 的同时，把叠加时间戳之后的同一路 I420 帧
+// 再次编码为 H.264 并通过 `rtsp://<host>:<port><mount>` 分发给局域网内的监视器，
+// 复用与 LiveKit 路径相同的一次采集，不重复打开摄像头/RTSP 源。
+
+use anyhow::{Context, Result};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use gstreamer_rtsp_server as gst_rtsp_server;
+use gstreamer_rtsp_server::prelude::*;
+use std::sync::{Arc, Mutex};
+
+/// 已启动的内置 RTSP 服务器句柄，主循环通过它喂入帧。
+pub struct RtspServerHandle {
+    appsrc: Arc<Mutex<Option<gst_app::AppSrc>>>,
+    caps_set: Arc<Mutex<bool>>,
+}
+
+impl RtspServerHandle {
+    /// 推送一帧 I420 数据到所有已连接的 RTSP 客户端；未建立媒体会话前静默丢弃。
+    pub fn push_i420(&self, y: &[u8], u: &[u8], v: &[u8], width: u32, height: u32, ts_us: i64) {
+        let guard = match self.appsrc.lock() {
+            Ok(g) => g,
+            Err(_) => return,
+        };
+        let Some(appsrc) = guard.as_ref() else { return };
+
+        let mut caps_set = self.caps_set.lock().unwrap();
+        if !*caps_set {
+            let caps = gst::Caps::builder("video/x-raw")
+                .field("format", "I420")
+                .field("width", width as i32)
+                .field("height", height as i32)
+                .field("framerate", gst::Fraction::new(0, 1))
+                .build();
+            appsrc.set_caps(Some(&caps));
+            *caps_set = true;
+        }
+        drop(caps_set);
+
+        let total_len = y.len() + u.len() + v.len();
+        let Ok(mut buffer) = gst::Buffer::with_size(total_len) else { return };
+        {
+            let buffer_mut = buffer.get_mut().unwrap();
+            buffer_mut.set_pts(gst::ClockTime::from_useconds(ts_us.max(0) as u64));
+            if let Ok(mut map) = buffer_mut.map_writable() {
+                let dst = map.as_mut_slice();
+                dst[0..y.len()].copy_from_slice(y);
+                dst[y.len()..y.len() + u.len()].copy_from_slice(u);
+                dst[y.len() + u.len()..].copy_from_slice(v);
+            }
+        }
+        let _ = appsrc.push_buffer(buffer);
+    }
+}
+
+/// 启动内置 RTSP 服务器，监听 `0.0.0.0:<port>`，挂载点为 `mount_path`，
+/// 可选用户名/密码启用 HTTP Basic 鉴权。
+pub fn start_rtsp_server(port: u16, mount_path: &str, user: Option<String>, pass: Option<String>) -> Result<RtspServerHandle> {
+    gst::init().context("GStreamer 初始化失败")?;
+
+    let server = gst_rtsp_server::RTSPServer::new();
+    server.set_service(&port.to_string());
+
+    let mounts = server.mount_points().context("获取 RTSP 挂载点表失败")?;
+    let factory = gst_rtsp_server::RTSPMediaFactory::new();
+    factory.set_launch(
+        "( appsrc name=src is-live=true format=time ! videoconvert ! \
+           x264enc tune=zerolatency speed-preset=veryfast key-int-max=30 ! \
+           rtph264pay name=pay0 pt=96 )",
+    );
+    factory.set_shared(true);
+
+    let appsrc_slot: Arc<Mutex<Option<gst_app::AppSrc>>> = Arc::new(Mutex::new(None));
+    let appsrc_slot_cb = appsrc_slot.clone();
+    factory.connect_media_configure(move |_factory, media| {
+        if let Some(bin) = media.element().dynamic_cast_ref::<gst::Bin>() {
+            if let Some(src) = bin.by_name_recurse_up("src") {
+                if let Ok(appsrc) = src.downcast::<gst_app::AppSrc>() {
+                    *appsrc_slot_cb.lock().unwrap() = Some(appsrc);
+                }
+            }
+        }
+    });
+
+    mounts.add_factory(mount_path, factory);
+
+    if let (Some(user), Some(pass)) = (user, pass) {
+        let auth = gst_rtsp_server::RTSPAuth::new();
+        let token = gst_rtsp_server::RTSPToken::new(&[(*gst_rtsp_server::RTSP_TOKEN_MEDIA_FACTORY_ROLE, &"viewer")]);
+        let basic = gst_rtsp_server::RTSPAuth::make_basic(&user, &pass);
+        auth.add_basic(basic.as_str(), &token);
+        server.set_auth(Some(&auth));
+    }
+
+    server.attach(None).context("RTSP 服务器挂载到主循环失败")?;
+
+    // gst-rtsp-server 依赖 GLib 主循环驱动；用独立线程运行，不与 tokio 运行时互相阻塞。
+    std::thread::spawn(|| {
+        let main_loop = glib::MainLoop::new(None, false);
+        main_loop.run();
+    });
+
+    println!("✅ 内置 RTSP 服务器已启动: rtsp://0.0.0.0:{}{}", port, mount_path);
+
+    Ok(RtspServerHandle { appsrc: appsrc_slot, caps_set: Arc::new(Mutex::new(false)) })
+}
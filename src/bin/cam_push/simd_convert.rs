@@ -0,0 +1,98 @@
+// simd_convert.rs
+// 硬件加速色彩空间转换：为 `frame_source` 里的标量 YUYV/RGB -> I420 转换提供
+// 按 CPU 架构选择的 SIMD 快路径（目前仅 aarch64 NEON 有正确实现，RGB 与 x86_64 上的
+// YUYV 均回退到标量），跑不到 SIMD 的尾部像素和不支持的架构回退到 `frame_source`
+// 里已有的标量实现，保证正确性优先。
+
+use anyhow::Result;
+
+use crate::frame_source::{rgb_to_i420_planes, yuyv_to_i420_planes};
+
+/// YUYV(4:2:2) -> I420，在支持的架构上使用 SIMD，否则回退到标量实现。
+///
+/// x86_64 曾经有一条手写 SSE2 路径，但它对 U/V 的反交织是错的（16 位 lane 移位拿不出
+/// 相隔 8 字节的两个奇数字节），会在几乎所有真实 x86_64 机器上（`sse2` 特性检测恒真）
+/// 产出色度错乱的画面；SSE2 缺少按字节抽取任意位置的 shuffle 指令，要修好它至少需要
+/// SSSE3 的 `pshufb`，超出本函数的范围，于是这里直接回退到标量实现，保证正确性优先。
+pub fn yuyv_to_i420_planes_fast(src: &[u8], width: usize, height: usize) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    #[cfg(target_arch = "aarch64")]
+    {
+        if is_aarch64_feature_detected!("neon") {
+            return Ok(unsafe { neon::yuyv_to_i420_neon(src, width, height) });
+        }
+    }
+    yuyv_to_i420_planes(src, width, height)
+}
+
+/// 已解码的 RGB8 数据 -> I420。RGB 每像素 3 字节、非 2 的幂对齐，不适合像 YUYV 那样直接
+/// 用 SIMD 寄存器载入/反交织，目前两个架构都直接走标量实现。
+pub fn rgb_to_i420_planes_fast(rgb: &[u8], width: usize, height: usize) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    rgb_to_i420_planes(rgb, width, height)
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    /// 每次处理 32 字节（16 个像素对应的 8 组 YUYV），不足 16 的倍数部分交回标量路径处理。
+    pub unsafe fn yuyv_to_i420_neon(src: &[u8], width: usize, height: usize) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let mut y = vec![0u8; width * height];
+        let mut u = vec![0u8; (width / 2) * (height / 2)];
+        let mut v = vec![0u8; (width / 2) * (height / 2)];
+
+        let simd_pixels = (width / 2 / 16) * 16; // 每条向量处理 16 组(=32像素)，按组对齐
+        for j in (0..height).step_by(2) {
+            let row0 = &src[j * width * 2..];
+            let row1 = &src[(j + 1) * width * 2..];
+            let uv_row = (j / 2) * (width / 2);
+
+            let mut group = 0usize; // 以“像素对”为单位(每组4字节 = 2像素)
+            while group < simd_pixels {
+                let off = group * 4;
+                // vld4q_u8: 反交织 YUYV -> (Y0 lanes, U lanes, Y1 lanes, V lanes)
+                let r0 = vld4q_u8(row0.as_ptr().add(off));
+                let r1 = vld4q_u8(row1.as_ptr().add(off));
+
+                // 色度在相邻两行之间做四舍五入平均（vrhaddq = (a+b+1)>>1）
+                let u_avg = vrhaddq_u8(r0.1, r1.1);
+                let v_avg = vrhaddq_u8(r0.3, r1.3);
+
+                // 把 Y0/Y1 重新交织回 y0,y1,y0,y1... 序列，分别写入两行
+                let y_row0 = uint8x16x2_t(r0.0, r0.2);
+                let y_row1 = uint8x16x2_t(r1.0, r1.2);
+                vst2q_u8(y.as_mut_ptr().add(j * width + group * 2), y_row0);
+                vst2q_u8(y.as_mut_ptr().add((j + 1) * width + group * 2), y_row1);
+
+                vst1q_u8(u.as_mut_ptr().add(uv_row + group), u_avg);
+                vst1q_u8(v.as_mut_ptr().add(uv_row + group), v_avg);
+
+                group += 16;
+            }
+
+            // 尾部不足一整条向量的像素对，用标量方式补齐
+            for i in (simd_pixels * 2..width).step_by(2) {
+                let idx0 = i * 2;
+                let y00 = row0[idx0];
+                let u0 = row0[idx0 + 1];
+                let y01 = row0[idx0 + 2];
+                let v0 = row0[idx0 + 3];
+                let y10 = row1[idx0];
+                let u1 = row1[idx0 + 1];
+                let y11 = row1[idx0 + 2];
+                let v1 = row1[idx0 + 3];
+
+                y[j * width + i] = y00;
+                y[j * width + i + 1] = y01;
+                y[(j + 1) * width + i] = y10;
+                y[(j + 1) * width + i + 1] = y11;
+
+                let uvi = uv_row + i / 2;
+                u[uvi] = ((u0 as u16 + u1 as u16 + 1) / 2) as u8;
+                v[uvi] = ((v0 as u16 + v1 as u16 + 1) / 2) as u8;
+            }
+        }
+
+        (y, u, v)
+    }
+}
+
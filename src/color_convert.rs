@@ -0,0 +1,107 @@
+// color_convert.rs
+// 将常见的 ROS2 Image 编码在进程内转换为 I420，
+// 使同一条 LiveKit 推流路径可以接收 NV12 / YUYV / RGB8 / BGR8 等常见相机输出。
+
+/// NV12（半平面 4:2:0）转 I420：Y 平面原样保留，交织的 UV 分量解交织为独立的 U/V 平面。
+/// `step` 为 Y 平面每行的字节步长（可能大于 `width`）。
+pub fn nv12_to_i420(data: &[u8], width: usize, height: usize, step: usize) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let mut y = vec![0u8; width * height];
+    for row in 0..height {
+        let src_off = row * step;
+        let dst_off = row * width;
+        y[dst_off..dst_off + width].copy_from_slice(&data[src_off..src_off + width]);
+    }
+
+    let uv_w = width / 2;
+    let uv_h = height / 2;
+    let uv_start = step * height;
+    let uv_step = step; // NV12 的 UV 平面每行字节数与 Y 平面一致（每像素对占 2 字节）
+    let mut u = vec![0u8; uv_w * uv_h];
+    let mut v = vec![0u8; uv_w * uv_h];
+    for row in 0..uv_h {
+        let src_row = uv_start + row * uv_step;
+        for col in 0..uv_w {
+            let i = src_row + col * 2;
+            u[row * uv_w + col] = data[i];
+            v[row * uv_w + col] = data[i + 1];
+        }
+    }
+    (y, u, v)
+}
+
+/// YUYV（打包 4:2:2，字节序 Y0 U Y1 V）转 I420，按 2x2 块垂直平均色度得到 4:2:0。
+/// `step` 为每行字节步长（可能大于 `width * 2`）。
+pub fn yuyv_to_i420(data: &[u8], width: usize, height: usize, step: usize) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let mut y = vec![0u8; width * height];
+    let uv_w = width / 2;
+    let uv_h = height / 2;
+    let mut u = vec![0u8; uv_w * uv_h];
+    let mut v = vec![0u8; uv_w * uv_h];
+
+    for j in (0..height).step_by(2) {
+        let row0 = j * step;
+        let row1 = (j + 1) * step;
+        for i in (0..width).step_by(2) {
+            let idx0 = row0 + i * 2;
+            let idx1 = row1 + i * 2;
+
+            let y00 = data[idx0];
+            let u0 = data[idx0 + 1];
+            let y01 = data[idx0 + 2];
+            let v0 = data[idx0 + 3];
+            let y10 = data[idx1];
+            let u1 = data[idx1 + 1];
+            let y11 = data[idx1 + 2];
+            let v1 = data[idx1 + 3];
+
+            y[j * width + i] = y00;
+            y[j * width + i + 1] = y01;
+            y[(j + 1) * width + i] = y10;
+            y[(j + 1) * width + i + 1] = y11;
+
+            let uvi = (j / 2) * uv_w + (i / 2);
+            u[uvi] = ((u0 as u16 + u1 as u16) / 2) as u8;
+            v[uvi] = ((v0 as u16 + v1 as u16) / 2) as u8;
+        }
+    }
+    (y, u, v)
+}
+
+/// RGB8/BGR8（紧凑 3 字节像素）转 I420，按 BT.601 全范围公式逐像素计算 Y，
+/// 并对每个 2x2 块平均得到 U/V。`swap_rb` 为 true 时按 BGR8 顺序读取。
+/// `step` 为每行字节步长（可能大于 `width * 3`）。
+pub fn rgb_to_i420(data: &[u8], width: usize, height: usize, step: usize, swap_rb: bool) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let mut y = vec![0u8; width * height];
+    let uv_w = width / 2;
+    let uv_h = height / 2;
+    let mut u = vec![0u8; uv_w * uv_h];
+    let mut v = vec![0u8; uv_w * uv_h];
+
+    let pixel_at = |row: usize, col: usize| -> (f32, f32, f32) {
+        let base = row * step + col * 3;
+        let p0 = data[base] as f32;
+        let p1 = data[base + 1] as f32;
+        let p2 = data[base + 2] as f32;
+        if swap_rb { (p2, p1, p0) } else { (p0, p1, p2) }
+    };
+
+    for j in (0..height).step_by(2) {
+        for i in (0..width).step_by(2) {
+            let mut u_acc = 0.0f32;
+            let mut v_acc = 0.0f32;
+            for dy in 0..2 {
+                for dx in 0..2 {
+                    let (r, g, b) = pixel_at(j + dy, i + dx);
+                    let y_val = 0.257 * r + 0.504 * g + 0.098 * b + 16.0;
+                    y[(j + dy) * width + (i + dx)] = y_val.round().clamp(0.0, 255.0) as u8;
+                    u_acc += -0.148 * r - 0.291 * g + 0.439 * b + 128.0;
+                    v_acc += 0.439 * r - 0.368 * g - 0.071 * b + 128.0;
+                }
+            }
+            let uvi = (j / 2) * uv_w + (i / 2);
+            u[uvi] = (u_acc / 4.0).round().clamp(0.0, 255.0) as u8;
+            v[uvi] = (v_acc / 4.0).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    (y, u, v)
+}
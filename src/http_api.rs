@@ -0,0 +1,147 @@
+// http_api.rs
+// 嵌入式管理/控制 REST API：暴露运行时统计信息，并允许在不重启进程的情况下
+// 调整发布码率/帧率，或重新订阅 ROS2 图像话题。
+
+use axum::{
+    extract::State,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// 控制平面收到的变更请求，经由通道送回 tokio 主循环执行，
+/// 避免 HTTP 处理协程直接持有 `Room`/订阅线程句柄。
+pub enum ApiCommand {
+    SetImageTopic { topic: String },
+}
+
+/// 运行时统计与配置的共享状态，由主循环和帧推送路径更新。
+pub struct ApiState {
+    pub room_name: String,
+    pub participant_count: AtomicU64,
+    pub frames_pushed: AtomicU64,
+    pub frames_dropped: AtomicU64,
+    pub last_frame_ts_us: AtomicI64,
+    pub current_max_bitrate: AtomicU64,
+    pub current_max_framerate: AtomicU32, // 存储为 framerate*1000 的整数，避免原子浮点
+    pub cmd_tx: mpsc::Sender<ApiCommand>,
+}
+
+impl ApiState {
+    pub fn new(room_name: String, max_bitrate: u64, max_framerate: f64, cmd_tx: mpsc::Sender<ApiCommand>) -> Self {
+        Self {
+            room_name,
+            participant_count: AtomicU64::new(0),
+            frames_pushed: AtomicU64::new(0),
+            frames_dropped: AtomicU64::new(0),
+            last_frame_ts_us: AtomicI64::new(0),
+            current_max_bitrate: AtomicU64::new(max_bitrate),
+            current_max_framerate: AtomicU32::new((max_framerate * 1000.0) as u32),
+            cmd_tx,
+        }
+    }
+
+    pub fn record_frame_pushed(&self, ts_us: i64) {
+        self.frames_pushed.fetch_add(1, Ordering::Relaxed);
+        self.last_frame_ts_us.store(ts_us, Ordering::Relaxed);
+    }
+
+    pub fn record_frame_dropped(&self) {
+        self.frames_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Serialize)]
+struct StatResponse {
+    room_name: String,
+    participant_count: u64,
+    frames_pushed: u64,
+    frames_dropped: u64,
+    last_frame_ts_us: i64,
+    max_bitrate: u64,
+    max_framerate: f64,
+    server_time_ms: u128,
+}
+
+#[derive(Deserialize)]
+struct SetVideoEncodingRequest {
+    max_bitrate: u64,
+    max_framerate: f64,
+}
+
+#[derive(Deserialize)]
+struct SetImageTopicRequest {
+    topic: String,
+}
+
+#[derive(Serialize)]
+struct AckResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+async fn get_stat(State(state): State<Arc<ApiState>>) -> Json<StatResponse> {
+    Json(StatResponse {
+        room_name: state.room_name.clone(),
+        participant_count: state.participant_count.load(Ordering::Relaxed),
+        frames_pushed: state.frames_pushed.load(Ordering::Relaxed),
+        frames_dropped: state.frames_dropped.load(Ordering::Relaxed),
+        last_frame_ts_us: state.last_frame_ts_us.load(Ordering::Relaxed),
+        max_bitrate: state.current_max_bitrate.load(Ordering::Relaxed),
+        max_framerate: state.current_max_framerate.load(Ordering::Relaxed) as f64 / 1000.0,
+        server_time_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis(),
+    })
+}
+
+/// livekit-rust 当前未提供已发布轨道的码率/帧率热更新接口（renegotiate/restart_track 之外
+/// 没有暴露按 RTP 发送端限速的 API），因此这里如实返回不支持，而不是假装已生效——
+/// 此前的实现会把请求值写进 `ApiState`（`/index/stat` 据此上报），但从未真正作用到轨道上，
+/// 调用方据此以为码率已经改变，这是比直接报错更危险的"看起来成功"。
+async fn set_video_encoding(
+    State(_state): State<Arc<ApiState>>,
+    Json(_req): Json<SetVideoEncodingRequest>,
+) -> Json<AckResponse> {
+    Json(AckResponse {
+        ok: false,
+        reason: Some("当前版本不支持已发布轨道的码率/帧率热更新".to_string()),
+    })
+}
+
+async fn set_image_topic(
+    State(state): State<Arc<ApiState>>,
+    Json(req): Json<SetImageTopicRequest>,
+) -> Json<AckResponse> {
+    let ok = state.cmd_tx.send(ApiCommand::SetImageTopic { topic: req.topic }).await.is_ok();
+    Json(AckResponse { ok, reason: None })
+}
+
+/// 启动管理 API（`/index/stat`、`/index/setVideoEncoding`、`/index/setImageTopic`），
+/// 监听 `HTTP_API_PORT`（默认 8088）。
+pub async fn start_http_api(state: Arc<ApiState>, port: u16) {
+    let app = Router::new()
+        .route("/index/stat", get(get_stat))
+        .route("/index/setVideoEncoding", post(set_video_encoding))
+        .route("/index/setImageTopic", post(set_image_topic))
+        .with_state(state);
+
+    let addr = format!("0.0.0.0:{}", port);
+    match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => {
+            println!("🛠️  管理 API 已启动: http://{}", addr);
+            if let Err(e) = axum::serve(listener, app).await {
+                eprintln!("⚠️  管理 API 服务退出: {:?}", e);
+            }
+        }
+        Err(e) => {
+            eprintln!("⚠️  管理 API 端口绑定失败 ({}): {:?}", addr, e);
+        }
+    }
+}
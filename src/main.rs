@@ -6,9 +6,13 @@ use livekit::prelude::*;
 use livekit::options::{TrackPublishOptions, VideoEncoding};
 use livekit::webrtc::video_frame::{VideoFrame, VideoRotation, I420Buffer};
 use livekit::webrtc::video_source::{RtcVideoSource, native::NativeVideoSource};
+use livekit::webrtc::video_stream::native::NativeVideoStream;
+use futures::StreamExt;
 use std::env;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc as std_mpsc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
 use tracing::{info, warn};
 use tracing_subscriber::EnvFilter;
@@ -21,9 +25,33 @@ use rclrs::{CreateBasicExecutor, RclrsErrorFilter};
 use sensor_msgs::msg::Image as RosImage;
 use std_msgs::msg::String as RosString;
 
+mod rtsp_source;
+use rtsp_source::start_rtsp_video_source;
+
+mod color_convert;
+use color_convert::{nv12_to_i420, rgb_to_i420, yuyv_to_i420};
+
+mod http_api;
+use http_api::{start_http_api, ApiCommand, ApiState};
+
+mod webhook;
+use webhook::{spawn_webhook_task, WebhookEvent};
+
+mod recorder;
+use recorder::{start_recorder, RecordFrame};
+
 // 全局视频源，用于从 GStreamer 线程安全地推送视频帧
 static GLOBAL_VIDEO_SOURCE: std::sync::OnceLock<Arc<RtcVideoSource>> = std::sync::OnceLock::new();
 
+// 全局 API 状态，供帧推送路径上报统计数据
+static GLOBAL_API_STATE: std::sync::OnceLock<Arc<ApiState>> = std::sync::OnceLock::new();
+
+// 全局 Webhook 发送端，供 ROS2 控制发布线程等非 async 上下文投递事件
+static GLOBAL_WEBHOOK_TX: std::sync::OnceLock<mpsc::Sender<WebhookEvent>> = std::sync::OnceLock::new();
+
+// 全局房间名，供非 async 线程在投递 Webhook 事件时携带
+static GLOBAL_ROOM_NAME: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
 // 全局控制状态，用于合并 gear 和 analog 消息
 static GLOBAL_CONTROL_STATE: std::sync::OnceLock<std::sync::Mutex<UnifiedControlMessage>> = std::sync::OnceLock::new();
 
@@ -47,6 +75,15 @@ enum ControlMsg {
     },
 }
 
+/// 反向视频帧：从远端 LiveKit 视频轨道解出的 I420 数据，准备发布为 sensor_msgs/Image
+struct OutputFrameMsg {
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    step: u32,
+    ts_us: i64,
+}
+
 /// 统一控制消息结构（合并 gear 和 analog）
 #[derive(serde::Serialize, serde::Deserialize)]
 struct UnifiedControlMessage {
@@ -73,7 +110,11 @@ struct UnifiedControlMessage {
 
 // 仅保留 ROS2 订阅路径（无 GStreamer 路径）
 
-fn start_ros2_image_subscriber(tx: mpsc::Sender<FrameMsg>, topic: String) -> std::thread::JoinHandle<()> {
+fn start_ros2_image_subscriber(
+    tx: mpsc::Sender<FrameMsg>,
+    topic: String,
+    stop_flag: Arc<AtomicBool>,
+) -> std::thread::JoinHandle<()> {
     std::thread::spawn(move || {
         // 基于当前 rclrs 版本的推荐写法：Context -> Executor -> Node -> Subscription -> spin
         let mut executor = match rclrs::Context::default_from_env() {
@@ -98,51 +139,67 @@ fn start_ros2_image_subscriber(tx: mpsc::Sender<FrameMsg>, topic: String) -> std
             let enc = msg.encoding.to_lowercase();
             let data_len = msg.data.len();
 
-            if enc != "i420" {
-                println!(
-                    "⚠️  收到非 I420 编码: enc='{}' (len={}), w={}, h={}, step={}",
-                    msg.encoding, data_len, width, height, step
-                );
-                return;
-            }
-
             let ts_us = (msg.header.stamp.sec as i64) * 1_000_000 + (msg.header.stamp.nanosec as i64) / 1_000;
 
+            let (y, u, v): (Arc<[u8]>, Arc<[u8]>, Arc<[u8]>) = match enc.as_str() {
+                "i420" => {
+                    // 纯 I420 输入保留原有零拷贝快路径
+                    let y_size = (width as usize) * (height as usize);
+                    let uv_plane = (width as usize * height as usize) / 4;
+                    let expected = y_size + 2 * uv_plane;
+
+                    if data_len < expected {
+                        println!(
+                            "⚠️  I420 数据长度不足: got={}, expected={} (w={}, h={}, step={})",
+                            data_len, expected, width, height, step
+                        );
+                        return;
+                    }
 
-            // 处理 I420 格式（原有逻辑）
-            let y_size = (width as usize) * (height as usize);
-            let uv_plane = (width as usize * height as usize) / 4;
-            let expected = y_size + 2 * uv_plane;
-
-            if data_len < expected {
-                println!(
-                    "⚠️  I420 数据长度不足: got={}, expected={} (w={}, h={}, step={})",
-                    data_len, expected, width, height, step
-                );
-                return;
-            }
-
-            if step != width {
-                println!(
-                    "⚠️  发现 stride(每行步长) 与 width 不一致: step={} != width={}，需按行拷贝平面。",
-                    step, width
-                );
-            }
+                    if step != width {
+                        println!(
+                            "⚠️  发现 stride(每行步长) 与 width 不一致: step={} != width={}，需按行拷贝平面。",
+                            step, width
+                        );
+                    }
 
-            // 零拷贝优化：使用Arc::from避免to_vec()复制
-            let y = Arc::from(&msg.data[0..y_size]);
-            let u = Arc::from(&msg.data[y_size..y_size + uv_plane]);
-            let v = Arc::from(&msg.data[y_size + uv_plane..expected]);
+                    // 零拷贝优化：使用Arc::from避免to_vec()复制
+                    (
+                        Arc::from(&msg.data[0..y_size]),
+                        Arc::from(&msg.data[y_size..y_size + uv_plane]),
+                        Arc::from(&msg.data[y_size + uv_plane..expected]),
+                    )
+                }
+                "nv12" => {
+                    let (y, u, v) = nv12_to_i420(&msg.data, width as usize, height as usize, step as usize);
+                    (Arc::from(y), Arc::from(u), Arc::from(v))
+                }
+                "yuyv" | "yuy2" => {
+                    let (y, u, v) = yuyv_to_i420(&msg.data, width as usize, height as usize, step as usize);
+                    (Arc::from(y), Arc::from(u), Arc::from(v))
+                }
+                "rgb8" | "bgr8" => {
+                    let (y, u, v) = rgb_to_i420(&msg.data, width as usize, height as usize, step as usize, enc == "bgr8");
+                    (Arc::from(y), Arc::from(u), Arc::from(v))
+                }
+                _ => {
+                    println!(
+                        "⚠️  收到不支持的编码: enc='{}' (len={}), w={}, h={}, step={}",
+                        msg.encoding, data_len, width, height, step
+                    );
+                    return;
+                }
+            };
 
             // 视频帧日志过多，开发阶段关闭此高频打印，如需调试可启用
 
-            if let Err(e) = tx_sub.try_send(FrameMsg::I420 { 
-                y, 
-                u, 
-                v, 
-                width, 
-                height, 
-                ts_us 
+            if let Err(e) = tx_sub.try_send(FrameMsg::I420 {
+                y,
+                u,
+                v,
+                width,
+                height,
+                ts_us
             }) {
                 println!("⚠️  发送到通道失败(满?): {:?}", e);
             }
@@ -157,10 +214,77 @@ fn start_ros2_image_subscriber(tx: mpsc::Sender<FrameMsg>, topic: String) -> std
             }
         };
 
-        println!("🔄 即将进入 ROS2 spin()");
-        let errs = executor.spin(rclrs::SpinOptions::default());
-        if let Err(e) = errs.first_error() {
-            eprintln!("ROS2 spin failed: {:?}", e);
+        println!("🔄 即将进入 ROS2 spin() (topic='{}')", topic);
+        // 用短超时反复 spin 并检查停止标志，而不是一次性阻塞到底：
+        // 切换话题时需要能让旧订阅者的线程（及其 node/subscription）真正退出，
+        // 否则旧话题会一直把帧灌进同一个 tx，和新话题的帧交错。
+        while !stop_flag.load(Ordering::Relaxed) {
+            let errs = executor.spin(rclrs::SpinOptions {
+                timeout: Some(Duration::from_millis(200)),
+                ..Default::default()
+            });
+            if let Err(e) = errs.first_error() {
+                eprintln!("ROS2 spin failed: {:?}", e);
+                break;
+            }
+        }
+        println!("🛑 ROS2 图像订阅线程退出: topic='{}'", topic);
+    })
+}
+
+/// 启动 ROS2 图像发布线程，将从远端 LiveKit 视频轨道收到的帧发布为 sensor_msgs/Image，
+/// 使单向遥操作视频链路可以反向把操作员侧画面（叠加层/摄像头）带回机器一侧。
+fn start_ros2_output_image_publisher(
+    rx: std_mpsc::Receiver<OutputFrameMsg>,
+    topic: String,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let executor = match rclrs::Context::default_from_env() {
+            Ok(ctx) => ctx.create_basic_executor(),
+            Err(e) => {
+                eprintln!("ROS2 Context init failed (output image): {:?}", e);
+                return;
+            }
+        };
+        let node = match executor.create_node("lk_ros_output_image_bridge") {
+            Ok(n) => n,
+            Err(e) => {
+                eprintln!("ROS2 Node create failed (output image): {:?}", e);
+                return;
+            }
+        };
+
+        let pub_image = match node.create_publisher::<RosImage>(&topic) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("Create publisher '{}' failed: {:?}", topic, e);
+                return;
+            }
+        };
+
+        println!("✅ ROS2 反向图像发布器已创建: '{}'", topic);
+
+        loop {
+            match rx.recv() {
+                Ok(OutputFrameMsg { data, width, height, step, ts_us }) => {
+                    let mut msg = RosImage::default();
+                    msg.width = width;
+                    msg.height = height;
+                    msg.step = step;
+                    msg.encoding = "i420".to_string();
+                    msg.header.stamp.sec = (ts_us / 1_000_000) as i32;
+                    msg.header.stamp.nanosec = ((ts_us % 1_000_000) * 1_000) as u32;
+                    msg.data = data;
+
+                    if let Err(e) = pub_image.publish(msg) {
+                        eprintln!("⚠️  发布 '{}' 失败: {:?}", topic, e);
+                    }
+                }
+                Err(_) => {
+                    println!("🛑 反向图像通道已关闭，结束 ROS2 反向图像发布线程");
+                    break;
+                }
+            }
         }
     })
 }
@@ -227,6 +351,13 @@ fn start_ros2_controls_publisher(
                         }
                     } else {
                         eprintln!("⚠️  解析控制消息失败，丢弃: {}", payload);
+                        if let Some(webhook_tx) = GLOBAL_WEBHOOK_TX.get() {
+                            let room = GLOBAL_ROOM_NAME.get().cloned().unwrap_or_default();
+                            let _ = webhook_tx.blocking_send(WebhookEvent::ControlParseFailed {
+                                room,
+                                raw_len: payload.len(),
+                            });
+                        }
                     }
                 }
                 Err(_) => {
@@ -371,9 +502,16 @@ fn push_i420_planes_sync(
             "⚠️  平面尺寸不匹配，丢弃帧: dst(Y,U,V)=({},{},{}), src(Y,U,V)=({},{},{}) w={}, h={}",
             y_data.len(), u_data.len(), v_data.len(), y_plane.len(), u_plane.len(), v_plane.len(), width, height
         );
+        if let Some(api_state) = GLOBAL_API_STATE.get() {
+            api_state.record_frame_dropped();
+        }
         return Ok(());
     }
 
+    if let Some(api_state) = GLOBAL_API_STATE.get() {
+        api_state.record_frame_pushed(timestamp_us);
+    }
+
     let frame = VideoFrame {
         rotation: VideoRotation::VideoRotation0,
         timestamp_us,
@@ -458,6 +596,16 @@ async fn main() -> Result<()> {
         .context("连接到 LiveKit 失败")?;
     info!("Connected to room: '{}'", room.name());
     println!("   ✅ 成功连接到房间: '{}'", room.name());
+    let _ = GLOBAL_ROOM_NAME.set(room.name().to_string());
+
+    let webhook_tx = spawn_webhook_task();
+    let _ = GLOBAL_WEBHOOK_TX.set(webhook_tx.clone());
+    let _ = webhook_tx
+        .send(WebhookEvent::RoomConnected {
+            room: room.name().to_string(),
+            username: env::var("LIVEKIT_USERNAME").unwrap_or_else(|_| "heavyMachRemoteTerm".to_string()),
+        })
+        .await;
 
     println!("🎥 创建并发布视频轨道...");
     let track_name = env::var("VIDEO_TRACK_NAME").unwrap_or_else(|_| "ros_camera_feed".to_string());
@@ -484,18 +632,65 @@ async fn main() -> Result<()> {
     info!(track = %track_name, "Published local video track");
     println!("   ✅ 视频轨道 '{}' 发布成功", track_name);
     let _ = GLOBAL_VIDEO_SOURCE.set(Arc::new(source));
+    let _ = webhook_tx
+        .send(WebhookEvent::VideoTrackPublished { room: room.name().to_string(), track_name: track_name.clone() })
+        .await;
 
-    // --- 仅 ROS2 视频源 ---
+    // --- 视频源：默认 ROS2 图像话题，可切换为 RTSP 拉流 ---
     let (tx, mut rx) = mpsc::channel::<FrameMsg>(64);
-    let topic = std::env::var("ROS_IMAGE_TOPIC").unwrap_or_else(|_| "/camera_front_wide".to_string());
-    println!("🛰️  使用 ROS2 图像话题: {}", topic);
-    let _handle = start_ros2_image_subscriber(tx, topic);
+    let video_source = std::env::var("VIDEO_SOURCE").unwrap_or_else(|_| "ros2".to_string());
+    let mut current_image_topic = std::env::var("ROS_IMAGE_TOPIC").unwrap_or_else(|_| "/camera_front_wide".to_string());
+    // 当前 ROS2 图像订阅线程的停止标志：切换话题时先置位让旧线程退出，再起新线程+新标志。
+    let mut image_sub_stop = Arc::new(AtomicBool::new(false));
+    let mut _handle = match video_source.as_str() {
+        "rtsp" => {
+            let rtsp_url = env::var("RTSP_URL").context("VIDEO_SOURCE=rtsp 需要设置 RTSP_URL")?;
+            println!("📡 使用 RTSP 视频源: {}", rtsp_url);
+            start_rtsp_video_source(tx.clone(), rtsp_url)
+        }
+        _ => {
+            println!("🛰️  使用 ROS2 图像话题: {}", current_image_topic);
+            start_ros2_image_subscriber(tx.clone(), current_image_topic.clone(), image_sub_stop.clone())
+        }
+    };
 
     // --- ROS2 控制发布器（接收 LiveKit DataChannel -> 统一发布到 ROS2 话题） ---
     let (ctl_tx, ctl_rx) = std_mpsc::channel::<ControlMsg>();
     let ros_control_topic = std::env::var("ROS_CONTROL_TOPIC").unwrap_or_else(|_| "/controls/teleop".to_string());
     let _ctl_handle = start_ros2_controls_publisher(ctl_rx, ros_control_topic.clone());
 
+    // --- 反向视频路径：订阅远端视频轨道 -> 发布到 ROS2 ---
+    let (out_img_tx, out_img_rx) = std_mpsc::channel::<OutputFrameMsg>();
+    let ros_output_image_topic = std::env::var("ROS_OUTPUT_IMAGE_TOPIC").unwrap_or_else(|_| "/camera_remote_overlay".to_string());
+    let _out_img_handle = start_ros2_output_image_publisher(out_img_rx, ros_output_image_topic);
+
+    // --- 管理/控制 REST API（运行时统计查询 + 码率/帧率/话题热更新） ---
+    let (api_cmd_tx, mut api_cmd_rx) = mpsc::channel::<ApiCommand>(16);
+    let api_state = Arc::new(ApiState::new(room.name().to_string(), 2_000_000, 20.0, api_cmd_tx));
+    let _ = GLOBAL_API_STATE.set(api_state.clone());
+    let api_port: u16 = env::var("HTTP_API_PORT").ok().and_then(|s| s.parse().ok()).unwrap_or(8088);
+    tokio::spawn(start_http_api(api_state.clone(), api_port));
+
+    // --- 可选本地 fMP4 录制（RECORD_DIR 设置后启用） ---
+    let record_tx: Option<mpsc::Sender<RecordFrame>> = match env::var("RECORD_DIR") {
+        Ok(dir) => {
+            let segment_secs: u64 = env::var("RECORD_SEGMENT_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(10);
+            match start_recorder(dir, segment_secs) {
+                Ok(tx) => Some(tx),
+                Err(e) => {
+                    eprintln!("⚠️  启动本地录制失败: {:?}", e);
+                    None
+                }
+            }
+        }
+        Err(_) => None,
+    };
+
+    // --- 帧饥饿看门狗：超过 N 秒没有新帧则上报一次（直至恢复后才可再次触发） ---
+    let starvation_secs: u64 = env::var("FRAME_STARVATION_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(5);
+    let mut starvation_ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+    let mut is_starving = false;
+    let mut first_frame_seen = false;
 
     // --- 主事件循环 ---
     println!("🔄 进入主事件循环 (按 Ctrl+C 停止)");
@@ -505,8 +700,11 @@ async fn main() -> Result<()> {
             Some(event) = room_events.recv() => {
                 info!(?event, "Received room event");
                 match event {
-                    RoomEvent::Disconnected { .. } => {
+                    RoomEvent::Disconnected { reason } => {
                         println!("   ❌ 房间连接已断开，程序即将退出。");
+                        let _ = webhook_tx
+                            .send(WebhookEvent::RoomDisconnected { room: room.name().to_string(), reason: format!("{:?}", reason) })
+                            .await;
                         break;
                     }
                     // DataChannel 数据（统一处理所有类型）
@@ -516,12 +714,61 @@ async fn main() -> Result<()> {
                         // 统一透传所有控制消息到 ROS2 发布线程
                         let _ = ctl_tx.send(ControlMsg::Data { data: payload, reliable });
                     }
+                    RoomEvent::ParticipantConnected(_) | RoomEvent::ParticipantDisconnected(_) => {
+                        let count = room.remote_participants().len() as u64;
+                        api_state.participant_count.store(count, std::sync::atomic::Ordering::Relaxed);
+                    }
+                    // 远端发布了视频轨道：订阅并转发到 ROS2（反向视频路径）
+                    RoomEvent::TrackSubscribed { track, publication: _, participant } => {
+                        if let RemoteTrack::Video(video_track) = track {
+                            println!("📺 订阅远端视频轨道: participant={}", participant.identity());
+                            let out_tx = out_img_tx.clone();
+                            tokio::spawn(async move {
+                                let rtc_track = video_track.rtc_track();
+                                let mut video_stream = NativeVideoStream::new(rtc_track);
+                                while let Some(frame) = video_stream.next().await {
+                                    let i420 = frame.buffer.to_i420();
+                                    let (y_data, u_data, v_data) = i420.data();
+                                    let width = i420.width();
+                                    let height = i420.height();
+                                    let mut data = Vec::with_capacity(y_data.len() + u_data.len() + v_data.len());
+                                    data.extend_from_slice(y_data);
+                                    data.extend_from_slice(u_data);
+                                    data.extend_from_slice(v_data);
+
+                                    if out_tx
+                                        .send(OutputFrameMsg {
+                                            data,
+                                            width,
+                                            height,
+                                            step: width,
+                                            ts_us: frame.timestamp_us,
+                                        })
+                                        .is_err()
+                                    {
+                                        break;
+                                    }
+                                }
+                                println!("🔚 远端视频轨道流结束");
+                            });
+                        }
+                    }
                     _ => {}
                 }
             }
             // 监听从 ROS2 图像订阅来的新视频帧（静默处理，避免刷屏）
             Some(msg) = rx.recv() => {
                 let FrameMsg::I420 { y, u, v, width, height, ts_us } = msg;
+                is_starving = false;
+                if !first_frame_seen {
+                    first_frame_seen = true;
+                    let _ = webhook_tx
+                        .send(WebhookEvent::FirstFrameReceived { room: room.name().to_string(), width, height })
+                        .await;
+                }
+                if let Some(rec_tx) = &record_tx {
+                    let _ = rec_tx.try_send(RecordFrame { y: y.clone(), u: u.clone(), v: v.clone(), width, height, ts_us });
+                }
                 // 在后台阻塞线程执行拷贝与提交，避免阻塞主异步循环
                 tokio::task::spawn_blocking(move || {
                     // 直接调用同步函数，避免 block_on 套娃
@@ -530,6 +777,42 @@ async fn main() -> Result<()> {
                     }
                 });
             }
+            // 帧饥饿看门狗：持续无帧达到阈值时上报一次，恢复后可再次触发
+            _ = starvation_ticker.tick() => {
+                if first_frame_seen {
+                    let last_ts_us = api_state.last_frame_ts_us.load(std::sync::atomic::Ordering::Relaxed);
+                    let last_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64;
+                    let elapsed_secs = ((last_ms * 1000 - last_ts_us).max(0) / 1_000_000) as u64;
+                    if elapsed_secs >= starvation_secs && !is_starving {
+                        is_starving = true;
+                        let dropped = api_state.frames_dropped.load(std::sync::atomic::Ordering::Relaxed);
+                        let _ = webhook_tx
+                            .send(WebhookEvent::FrameStarvation {
+                                room: room.name().to_string(),
+                                seconds_since_last_frame: elapsed_secs,
+                                dropped_frame_count: dropped,
+                            })
+                            .await;
+                    }
+                }
+            }
+            // 管理 API 下发的控制平面变更（图像话题；码率/帧率热更新见 http_api.rs 的说明）
+            Some(cmd) = api_cmd_rx.recv() => {
+                match cmd {
+                    ApiCommand::SetImageTopic { topic } => {
+                        if video_source == "ros2" && topic != current_image_topic {
+                            println!("🔁 切换 ROS2 图像话题: {} -> {}", current_image_topic, topic);
+                            current_image_topic = topic.clone();
+                            // 通知旧订阅者退出 spin 循环，避免它和新话题的订阅同时往 tx 灌帧
+                            image_sub_stop.store(true, Ordering::Relaxed);
+                            image_sub_stop = Arc::new(AtomicBool::new(false));
+                            _handle = start_ros2_image_subscriber(tx.clone(), topic, image_sub_stop.clone());
+                        } else {
+                            println!("⚠️  当前视频源非 ROS2 或话题未变化，忽略 setImageTopic");
+                        }
+                    }
+                }
+            }
             // 监听 Ctrl+C 信号以优雅地关闭
             _ = tokio::signal::ctrl_c() => {
                 info!("Ctrl+C received, shutting down.");
@@ -0,0 +1,140 @@
+// recorder.rs
+// 可选的本地 fMP4 录制子系统：将同一路推送给 LiveKit 的 I420 帧同时落盘为
+// 分片 MP4（fragmented MP4），由 `RECORD_DIR` 启用，提供与服务端录制相互独立的
+// 防篡改本地归档。
+
+use std::io::Write as _;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// 送入录制器的一帧，字段与 `FrameMsg::I420` 对齐，避免在调用方重复定义。
+pub struct RecordFrame {
+    pub y: Arc<[u8]>,
+    pub u: Arc<[u8]>,
+    pub v: Arc<[u8]>,
+    pub width: u32,
+    pub height: u32,
+    pub ts_us: i64,
+}
+
+/// 启动录制线程，返回用于投喂帧的发送端。
+///
+/// 流水线大致为：
+/// `appsrc ! videoconvert ! x264enc key-int-max=<segment_secs*fps> ! h264parse !
+///  splitmuxsink location=<dir>/segment_%05d.mp4 max-size-time=<segment_secs>`,
+/// `splitmuxsink`在每个关键帧处切分，且`mp4mux`以`streamable=true`提前写出
+/// `moov`/`moof` 初始化段，使未正常关闭时已写入的分片仍可回放。
+/// 每次切分都会追加一行到 `<dir>/segments.m3u8`，作为顺序回放的分片索引。
+pub fn start_recorder(record_dir: String, segment_secs: u64) -> anyhow::Result<mpsc::Sender<RecordFrame>> {
+    std::fs::create_dir_all(&record_dir)?;
+
+    let (tx, mut rx) = mpsc::channel::<RecordFrame>(64);
+
+    std::thread::spawn(move || {
+        if let Err(e) = gstreamer::init() {
+            eprintln!("⚠️  录制子系统 GStreamer 初始化失败: {:?}", e);
+            return;
+        }
+
+        let index_path = format!("{}/segments.m3u8", record_dir);
+        let index_file = Arc::new(std::sync::Mutex::new(
+            match std::fs::OpenOptions::new().create(true).append(true).open(&index_path) {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("⚠️  无法创建分片索引 {}: {:?}", index_path, e);
+                    return;
+                }
+            },
+        ));
+
+        // key-int-max 决定 GOP 长度，splitmuxsink 只能在关键帧处切分：取值过大会让实际
+        // 分片时长远超 segment_secs，违背分片录制“按固定时长可独立回放”的初衷。
+        let fps: u64 = std::env::var("RECORD_FPS").ok().and_then(|s| s.parse().ok()).unwrap_or(20);
+        let key_int_max = segment_secs.saturating_mul(fps).max(1);
+
+        let pipeline_str = format!(
+            "appsrc name=src is-live=true format=time block=true ! videoconvert ! \
+             x264enc tune=zerolatency speed-preset=veryfast key-int-max={} ! h264parse ! \
+             splitmuxsink name=mux muxer-factory=mp4mux muxer-properties=\"properties,streamable=true,fragment-duration=1000\" \
+             max-size-time={} location={}/segment_%05d.mp4",
+            key_int_max,
+            segment_secs.saturating_mul(1_000_000_000),
+            record_dir
+        );
+
+        let pipeline = match gstreamer::parse_launch(&pipeline_str) {
+            Ok(p) => match p.downcast::<gstreamer::Pipeline>() {
+                Ok(p) => p,
+                Err(_) => {
+                    eprintln!("⚠️  录制管道不是 Pipeline 类型");
+                    return;
+                }
+            },
+            Err(e) => {
+                eprintln!("⚠️  录制管道构建失败: {:?}", e);
+                return;
+            }
+        };
+
+        let appsrc = match pipeline.by_name("src").and_then(|e| e.downcast::<gstreamer_app::AppSrc>().ok()) {
+            Some(a) => a,
+            None => {
+                eprintln!("⚠️  未找到录制管道的 appsrc 元素");
+                return;
+            }
+        };
+
+        if let Some(mux) = pipeline.by_name("mux") {
+            let index_file = index_file.clone();
+            mux.connect("format-location", false, move |args| {
+                let location = args[1].get::<String>().unwrap_or_default();
+                if let Ok(mut f) = index_file.lock() {
+                    let _ = writeln!(f, "{}", location);
+                }
+                None
+            });
+        }
+
+        if let Err(e) = pipeline.set_state(gstreamer::State::Playing) {
+            eprintln!("⚠️  录制管道启动失败: {:?}", e);
+            return;
+        }
+        println!("🎬 本地录制已启动: dir={} segment={}s", record_dir, segment_secs);
+
+        let mut caps_set = false;
+        while let Some(frame) = rx.blocking_recv() {
+            if !caps_set {
+                let caps = gstreamer::Caps::builder("video/x-raw")
+                    .field("format", "I420")
+                    .field("width", frame.width as i32)
+                    .field("height", frame.height as i32)
+                    .field("framerate", gstreamer::Fraction::new(0, 1))
+                    .build();
+                appsrc.set_caps(Some(&caps));
+                caps_set = true;
+            }
+
+            let total_len = frame.y.len() + frame.u.len() + frame.v.len();
+            let mut buffer = gstreamer::Buffer::with_size(total_len).unwrap();
+            {
+                let buffer_mut = buffer.get_mut().unwrap();
+                buffer_mut.set_pts(gstreamer::ClockTime::from_useconds(frame.ts_us.max(0) as u64));
+                let mut map = buffer_mut.map_writable().unwrap();
+                let dst = map.as_mut_slice();
+                dst[0..frame.y.len()].copy_from_slice(&frame.y);
+                dst[frame.y.len()..frame.y.len() + frame.u.len()].copy_from_slice(&frame.u);
+                dst[frame.y.len() + frame.u.len()..].copy_from_slice(&frame.v);
+            }
+
+            if appsrc.push_buffer(buffer).is_err() {
+                eprintln!("⚠️  推送帧到录制管道失败");
+            }
+        }
+
+        let _ = appsrc.end_of_stream();
+        let _ = pipeline.set_state(gstreamer::State::Null);
+        println!("🛑 录制通道已关闭，录制管道已停止");
+    });
+
+    Ok(tx)
+}
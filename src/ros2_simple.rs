@@ -9,6 +9,12 @@ use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
+use v4l::io::traits::CaptureStream;
+
+mod actuator;
+use actuator::{gear_code, ActuatorConfig, ActuatorFrame, ActuatorSink, DryRunSink};
+
+mod v4l2_capture;
 
 /// 统一控制消息结构（与 Python 版本保持一致）
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -98,10 +104,66 @@ impl SimpleROS2Client {
         Ok(())
     }
     
-    /// 启动视频发布者
+    /// 启动视频发布者：打开本地 V4L2 摄像头（CAM_INDEX/CAM_WIDTH/CAM_HEIGHT/CAM_FOURCC 环境变量配置），
+    /// 按采集到的原始像素格式发布 sensor_msgs/Image，颜色空间转换交由下游订阅方处理
+    /// （参见 main.rs 的 start_ros2_image_subscriber，已支持 i420/nv12/yuyv/yuy2/rgb8/bgr8）。
+    /// 注意：仅支持非压缩格式，设置 CAM_FOURCC=MJPG 时此发布者无法工作。
     pub fn start_video_publisher(&self, topic: &str) -> Result<()> {
-        let _publisher = self.node.create_publisher::<RosImage>(topic)?;
+        let publisher = self.node.create_publisher::<RosImage>(topic)?;
         println!("✅ 视频发布者已启动: {}", topic);
+
+        let topic_name = topic.to_string();
+        std::thread::spawn(move || {
+            let cam_index: usize = std::env::var("CAM_INDEX").ok().and_then(|s| s.parse().ok()).unwrap_or(0);
+            let width: u32 = std::env::var("CAM_WIDTH").ok().and_then(|s| s.parse().ok()).unwrap_or(1280);
+            let height: u32 = std::env::var("CAM_HEIGHT").ok().and_then(|s| s.parse().ok()).unwrap_or(720);
+            let fourcc_str = std::env::var("CAM_FOURCC").unwrap_or_else(|_| "YUYV".to_string());
+            let encoding = fourcc_str.to_lowercase();
+
+            let (mut stream, width, height, _fourcc) =
+                match v4l2_capture::open_v4l2_stream(cam_index, width, height, &fourcc_str) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        eprintln!("⚠️  打开摄像头失败 (视频发布者 '{}'): {:?}", topic_name, e);
+                        return;
+                    }
+                };
+
+            println!("📷 视频发布者开始采集: device={}, {}x{}, encoding={}", cam_index, width, height, encoding);
+
+            loop {
+                let (buf, _meta) = match stream.next() {
+                    Ok(b) => b,
+                    Err(e) => {
+                        eprintln!("⚠️  读取采集帧失败: {:?}", e);
+                        continue;
+                    }
+                };
+
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+                let mut msg = RosImage::default();
+                msg.width = width;
+                msg.height = height;
+                // 每行步长取决于实际采集编码：YUYV 每像素 2 字节，NV12 的 Y 平面每像素 1 字节
+                // （main.rs 的 nv12_to_i420 按此 step 计算 UV 平面起始偏移，算错会导致越界或错位），
+                // RGB8/BGR8 每像素 3 字节。
+                let bytes_per_pixel: u32 = match encoding.as_str() {
+                    "nv12" => 1,
+                    "rgb8" | "bgr8" => 3,
+                    _ => 2, // yuyv/yuy2 等默认按 2 字节处理
+                };
+                msg.step = width * bytes_per_pixel;
+                msg.encoding = encoding.clone();
+                msg.header.stamp.sec = now.as_secs() as i32;
+                msg.header.stamp.nanosec = now.subsec_nanos();
+                msg.data = buf.to_vec();
+
+                if let Err(e) = publisher.publish(msg) {
+                    eprintln!("⚠️  发布 '{}' 失败: {:?}", topic_name, e);
+                }
+            }
+        });
+
         Ok(())
     }
     
@@ -122,6 +184,103 @@ impl SimpleROS2Client {
     pub fn get_control_state(&self) -> UnifiedControlMessage {
         self.control_state.lock().unwrap().clone()
     }
+
+    /// 获取控制状态的共享句柄，供执行器输出线程直接读取，无需每次轮询都经过 `SimpleROS2Client`。
+    pub fn control_state_handle(&self) -> Arc<Mutex<UnifiedControlMessage>> {
+        self.control_state.clone()
+    }
+}
+
+/// 固定频率地把最新控制状态映射为执行器指令并下发；超过 `cfg.watchdog_ms` 未收到新控制消息
+/// 时清零所有输出，避免失联后机器保持最后一次动作继续执行。
+pub fn run_actuator_loop(control_state: Arc<Mutex<UnifiedControlMessage>>, cfg: ActuatorConfig, mut sink: Box<dyn ActuatorSink>) {
+    let period = std::time::Duration::from_secs_f64(1.0 / cfg.rate_hz.max(1.0));
+    let mut was_stale = false;
+
+    loop {
+        std::thread::sleep(period);
+
+        let state = match control_state.lock() {
+            Ok(guard) => guard.clone(),
+            Err(_) => continue,
+        };
+
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        let age_ms = now_ms - state.timestamp;
+        let is_stale = state.timestamp <= 0 || age_ms > cfg.watchdog_ms;
+
+        let result = if is_stale {
+            if !was_stale {
+                eprintln!("⚠️  控制消息超过 {}ms 未更新，执行器输出已清零（看门狗触发）", cfg.watchdog_ms);
+            }
+            sink.zero()
+        } else {
+            let frame = ActuatorFrame {
+                rotation: cfg.rotation.apply(state.rotation),
+                throttle: cfg.throttle.apply(state.throttle),
+                brake: cfg.brake.apply(state.brake),
+                boom: cfg.boom.apply(state.boom),
+                bucket: cfg.bucket.apply(state.bucket),
+                swing: cfg.swing.apply(state.swing),
+                stick: cfg.stick.apply(state.stick),
+                left_track: cfg.left_track.apply(state.left_track),
+                right_track: cfg.right_track.apply(state.right_track),
+                gear: gear_code(&state.gear),
+            };
+            sink.apply(&frame)
+        };
+        was_stale = is_stale;
+
+        if let Err(e) = result {
+            eprintln!("⚠️  执行器下发失败: {:?}", e);
+        }
+    }
+}
+
+/// 启动执行器输出线程：按 `ACTUATOR_BACKEND` 环境变量选择后端（can|serial|dryrun，默认 dryrun），
+/// 读取对应连接参数并进入固定频率输出循环。
+pub fn start_actuator_output(control_state: Arc<Mutex<UnifiedControlMessage>>) -> std::thread::JoinHandle<()> {
+    let cfg = ActuatorConfig::default();
+    let backend = std::env::var("ACTUATOR_BACKEND").unwrap_or_else(|_| "dryrun".to_string());
+
+    std::thread::spawn(move || {
+        let sink: Box<dyn ActuatorSink> = match backend.as_str() {
+            #[cfg(feature = "socketcan")]
+            "can" => {
+                let iface = std::env::var("ACTUATOR_CAN_IFACE").unwrap_or_else(|_| "can0".to_string());
+                let base_id: u32 = std::env::var("ACTUATOR_CAN_BASE_ID")
+                    .ok()
+                    .and_then(|s| u32::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+                    .unwrap_or(0x300);
+                match actuator::CanActuatorSink::new(&iface, base_id) {
+                    Ok(sink) => Box::new(sink),
+                    Err(e) => {
+                        eprintln!("⚠️  CAN 执行器后端初始化失败，回退为 dry-run: {:?}", e);
+                        Box::new(DryRunSink)
+                    }
+                }
+            }
+            #[cfg(feature = "serialport")]
+            "serial" => {
+                let port = std::env::var("ACTUATOR_SERIAL_PORT").unwrap_or_else(|_| "/dev/ttyUSB0".to_string());
+                let baud: u32 = std::env::var("ACTUATOR_SERIAL_BAUD").ok().and_then(|s| s.parse().ok()).unwrap_or(115200);
+                match actuator::SerialActuatorSink::new(&port, baud) {
+                    Ok(sink) => Box::new(sink),
+                    Err(e) => {
+                        eprintln!("⚠️  串口执行器后端初始化失败，回退为 dry-run: {:?}", e);
+                        Box::new(DryRunSink)
+                    }
+                }
+            }
+            _ => Box::new(DryRunSink),
+        };
+
+        println!("✅ 执行器输出线程已启动: backend='{}', rate={}Hz, watchdog={}ms", backend, cfg.rate_hz, cfg.watchdog_ms);
+        run_actuator_loop(control_state, cfg, sink);
+    })
 }
 
 /// 解析控制消息
@@ -195,7 +354,10 @@ pub fn main() -> Result<()> {
     // 启动视频发布者
     let video_topic = std::env::var("ROS_IMAGE_TOPIC").unwrap_or_else(|_| "/camera_front_wide".to_string());
     client.start_video_publisher(&video_topic)?;
-    
+
+    // 启动执行器输出线程，把收到的控制状态实际下发给机器（CAN/串口/dry-run）
+    let _actuator_handle = start_actuator_output(client.control_state_handle());
+
     println!("✅ 客户端启动成功!");
     println!("📡 订阅控制话题: {}", control_topic);
     println!("📷 发布视频话题: {}", video_topic);
@@ -0,0 +1,150 @@
+// rtsp_source.rs
+// RTSP 拉流视频源：作为 ROS2 图像订阅之外的另一路帧生产者，
+// 解码 H.264/H.265 RTSP 流为 I420 后，复用与 ROS2 路径相同的 FrameMsg 通道。
+
+use crate::FrameMsg;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// 初始重连退避时长
+const RECONNECT_BACKOFF_MIN: Duration = Duration::from_millis(500);
+/// 最大重连退避时长
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(10);
+
+/// 启动 RTSP 视频源线程，将解码后的 I420 帧送入既有的 FrameMsg 通道。
+///
+/// 流水线大致为：
+/// `rtspsrc location=... latency=... ! rtph264depay ! avdec_h264 ! videoconvert ! video/x-raw,format=I420 ! appsink`
+/// 若使用 H.265 源，自动切换到 `rtph265depay ! avdec_h265`。
+///
+/// 断流或建流失败时按指数退避自动重连，不会让线程退出。
+pub fn start_rtsp_video_source(tx: mpsc::Sender<FrameMsg>, url: String) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        if let Err(e) = gst::init() {
+            eprintln!("⚠️  GStreamer 初始化失败: {:?}", e);
+            return;
+        }
+
+        let mut backoff = RECONNECT_BACKOFF_MIN;
+        loop {
+            match run_rtsp_pipeline(&tx, &url) {
+                Ok(()) => {
+                    // 正常到达流结束（EOS）或管道被显式停止，重置退避后重试
+                    backoff = RECONNECT_BACKOFF_MIN;
+                }
+                Err(e) => {
+                    eprintln!(
+                        "⚠️  RTSP 拉流异常，{:?} 后重连: url={} err={:?}",
+                        backoff, url, e
+                    );
+                }
+            }
+
+            if tx.is_closed() {
+                println!("🛑 帧通道已关闭，结束 RTSP 视频源线程");
+                return;
+            }
+
+            std::thread::sleep(backoff);
+            backoff = std::cmp::min(backoff * 2, RECONNECT_BACKOFF_MAX);
+        }
+    })
+}
+
+fn run_rtsp_pipeline(tx: &mpsc::Sender<FrameMsg>, url: &str) -> anyhow::Result<()> {
+    let codec = std::env::var("RTSP_CODEC").unwrap_or_else(|_| "h264".to_string());
+    let latency_ms: u32 = std::env::var("RTSP_LATENCY_MS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(200);
+
+    let (depay, decoder) = match codec.to_lowercase().as_str() {
+        "h265" | "hevc" => ("rtph265depay", "avdec_h265"),
+        _ => ("rtph264depay", "avdec_h264"),
+    };
+
+    let pipeline_str = format!(
+        "rtspsrc location={} latency={} ! {} ! {} ! videoconvert ! video/x-raw,format=I420 ! appsink name=sink sync=false max-buffers=2 drop=true",
+        url, latency_ms, depay, decoder
+    );
+
+    let pipeline = gst::parse_launch(&pipeline_str)?
+        .downcast::<gst::Pipeline>()
+        .map_err(|_| anyhow::anyhow!("解析后的元素不是 Pipeline"))?;
+
+    let appsink = pipeline
+        .by_name("sink")
+        .ok_or_else(|| anyhow::anyhow!("未找到 appsink 元素"))?
+        .downcast::<gst_app::AppSink>()
+        .map_err(|_| anyhow::anyhow!("sink 元素不是 AppSink"))?;
+
+    let tx_frame = tx.clone();
+    appsink.set_callbacks(
+        gst_app::AppSinkCallbacks::builder()
+            .new_sample(move |sink| {
+                let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+                let caps = sample.caps().ok_or(gst::FlowError::Error)?;
+                let info = gstreamer_video::VideoInfo::from_caps(caps).map_err(|_| gst::FlowError::Error)?;
+                let width = info.width();
+                let height = info.height();
+
+                let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+                let y_size = (width as usize) * (height as usize);
+                let uv_plane = y_size / 4;
+                let expected = y_size + 2 * uv_plane;
+                if map.len() < expected {
+                    println!(
+                        "⚠️  RTSP I420 buffer 长度不足: got={}, expected={}",
+                        map.len(),
+                        expected
+                    );
+                    return Ok(gst::FlowSuccess::Ok);
+                }
+
+                let y: Arc<[u8]> = Arc::from(&map[0..y_size]);
+                let u: Arc<[u8]> = Arc::from(&map[y_size..y_size + uv_plane]);
+                let v: Arc<[u8]> = Arc::from(&map[y_size + uv_plane..expected]);
+                // 用墙钟而非 buffer PTS 打时间戳：PTS 是管道相对时间（从流起始算起），
+                // 而 main.rs 的帧饥饿看门狗和 ApiState::last_frame_ts_us 都假设这是
+                // Unix 纪元微秒，两者混用会让看门狗在收到第一帧后就永久判定为断流。
+                let ts_us = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_micros() as i64)
+                    .unwrap_or(0);
+
+                if let Err(e) = tx_frame.try_send(FrameMsg::I420 { y, u, v, width, height, ts_us }) {
+                    println!("⚠️  RTSP 帧发送到通道失败(满?): {:?}", e);
+                }
+
+                Ok(gst::FlowSuccess::Ok)
+            })
+            .build(),
+    );
+
+    pipeline.set_state(gst::State::Playing)?;
+    println!("✅ RTSP 视频源已启动: {}", url);
+
+    let bus = pipeline.bus().ok_or_else(|| anyhow::anyhow!("管道无 bus"))?;
+    for msg in bus.iter_timed(gst::ClockTime::NONE) {
+        use gst::MessageView;
+        match msg.view() {
+            MessageView::Eos(_) => {
+                println!("🔄 RTSP 流到达 EOS，准备重连: {}", url);
+                break;
+            }
+            MessageView::Error(err) => {
+                let _ = pipeline.set_state(gst::State::Null);
+                anyhow::bail!("RTSP 管道错误: {} ({:?})", err.error(), err.debug());
+            }
+            _ => {}
+        }
+    }
+
+    let _ = pipeline.set_state(gst::State::Null);
+    Ok(())
+}
@@ -0,0 +1,41 @@
+// v4l2_capture.rs
+// 共享的 V4L2 设备打开 + 格式协商逻辑。`bin/cam_push` 的 `frame_source::V4l2Source` 和
+// `ros2_simple.rs` 的 ROS2 图像发布者都需要打开本地摄像头、协商宽高/FourCC、建立 mmap
+// 采集流——此前这段逻辑（含 `Box::leak` 的生命周期说明注释）在两处各写了一份，容易跑偏，
+// 这里提成一个函数供两边共用。
+
+use anyhow::{Context, Result};
+use v4l::buffer::Type;
+use v4l::io::mmap::Stream as MmapStream;
+use v4l::prelude::*;
+use v4l::video::Capture as _;
+
+/// 打开指定序号的 V4L2 设备、协商宽高/FourCC，并建立 mmap 采集流。
+///
+/// 返回协商后实际生效的宽高与 FourCC（设备可能不支持请求值而回退到最接近的格式）。
+/// `MmapStream` 的生命周期与 `Device` 绑定，而 `Device` 需要在整个采集过程中存活，
+/// 这里把它 leak 成 `'static` 引用以摆脱自引用结构的限制——调用方应只在进程生命周期内
+/// 为每路摄像头调用一次，每次调用都会泄漏一个 `Device`。
+pub fn open_v4l2_stream(
+    cam_index: usize,
+    width: u32,
+    height: u32,
+    fourcc_str: &str,
+) -> Result<(MmapStream<'static>, u32, u32, v4l::FourCC)> {
+    let mut dev = Device::new(cam_index).context("打开摄像头失败")?;
+
+    let mut fmt = dev.format().context("读取摄像头格式失败")?;
+    fmt.width = width;
+    fmt.height = height;
+    let mut fourcc_bytes = [0u8; 4];
+    for (i, b) in fourcc_str.bytes().take(4).enumerate() {
+        fourcc_bytes[i] = b;
+    }
+    fmt.fourcc = v4l::FourCC::new(&fourcc_bytes);
+    let fmt = dev.set_format(&fmt).context("设置摄像头格式失败")?;
+
+    let dev: &'static mut Device = Box::leak(Box::new(dev));
+    let stream = MmapStream::with_buffers(dev, Type::VideoCapture, 4).context("创建采集流失败")?;
+
+    Ok((stream, fmt.width, fmt.height, fmt.fourcc))
+}
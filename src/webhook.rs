@@ -0,0 +1,82 @@
+// webhook.rs
+// 生命周期事件 Webhook：连接/断开、轨道发布、首帧到达、长时间无帧（看门狗）、
+// 控制消息解析失败，均以 JSON POST 到 WEBHOOK_URL，便于舰队监控脱离 stdout 抓取。
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+static NEXT_EVENT_ID: AtomicU64 = AtomicU64::new(1);
+
+const MAX_RETRIES: u32 = 3;
+const RETRY_BASE_DELAY_MS: u64 = 300;
+
+/// 生命周期事件，携带事件特定字段，序列化时展开到顶层 JSON 对象。
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    RoomConnected { room: String, username: String },
+    RoomDisconnected { room: String, reason: String },
+    VideoTrackPublished { room: String, track_name: String },
+    FirstFrameReceived { room: String, width: u32, height: u32 },
+    FrameStarvation { room: String, seconds_since_last_frame: u64, dropped_frame_count: u64 },
+    ControlParseFailed { room: String, raw_len: usize },
+}
+
+#[derive(Serialize)]
+struct WebhookPayload {
+    event_id: u64,
+    timestamp_ms: u128,
+    #[serde(flatten)]
+    event: WebhookEvent,
+}
+
+/// 启动 Webhook 投递任务，返回用于提交事件的发送端。
+/// 若 `WEBHOOK_URL` 未设置，事件会被静默丢弃，不影响主流程。
+pub fn spawn_webhook_task() -> mpsc::Sender<WebhookEvent> {
+    let (tx, mut rx) = mpsc::channel::<WebhookEvent>(128);
+    let webhook_url = std::env::var("WEBHOOK_URL").ok();
+
+    tokio::spawn(async move {
+        let Some(url) = webhook_url else {
+            // 未配置 WEBHOOK_URL，排空通道以避免发送端阻塞
+            while rx.recv().await.is_some() {}
+            return;
+        };
+
+        let client = reqwest::Client::new();
+        while let Some(event) = rx.recv().await {
+            let payload = WebhookPayload {
+                event_id: NEXT_EVENT_ID.fetch_add(1, Ordering::Relaxed),
+                timestamp_ms: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis(),
+                event,
+            };
+
+            let mut attempt = 0;
+            loop {
+                match client.post(&url).json(&payload).send().await {
+                    Ok(resp) if resp.status().is_success() => break,
+                    Ok(resp) => {
+                        eprintln!("⚠️  Webhook 返回非 2xx: {}", resp.status());
+                    }
+                    Err(e) => {
+                        eprintln!("⚠️  Webhook 投递失败: {:?}", e);
+                    }
+                }
+
+                attempt += 1;
+                if attempt >= MAX_RETRIES {
+                    eprintln!("⚠️  Webhook 事件 {} 达到最大重试次数，放弃", payload.event_id);
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(RETRY_BASE_DELAY_MS * (1 << attempt))).await;
+            }
+        }
+    });
+
+    tx
+}